@@ -50,4 +50,7 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Compute the content root of a local file for `upload_file`.
+	ContentRoot(crate::content_root::ContentRootCmd),
 }
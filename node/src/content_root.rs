@@ -0,0 +1,72 @@
+//! Computes the content root of a local file, as passed to `upload_file`.
+
+use node_dropbox_runtime::pallet_mydropbox::merkle;
+use sp_core::H256;
+use std::{
+	fs::File,
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+/// The `content-root` command.
+#[derive(Debug, clap::Parser)]
+pub struct ContentRootCmd {
+	/// File to hash.
+	#[clap(parse(from_os_str))]
+	pub path: PathBuf,
+
+	/// Size in bytes of the chunks the file is split into.
+	#[clap(long, default_value = "65536")]
+	pub chunk_size: u32,
+}
+
+impl ContentRootCmd {
+	/// Prints the arguments `upload_file` expects for the file, and the content id the chain
+	/// stores the contents under.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let (root, file_size) = content_root(&self.path, self.chunk_size)?;
+		println!("content_root: {:?}", root);
+		println!("chunk_size: {}", self.chunk_size);
+		println!("file_size: {}", file_size);
+		println!("content_id: {:?}", merkle::content_id(&root, file_size, self.chunk_size));
+		Ok(())
+	}
+}
+
+/// Returns the Merkle root over the `chunk_size`-byte chunks of the file at `path`, along with
+/// the file size.
+pub fn content_root(path: &Path, chunk_size: u32) -> io::Result<(H256, u64)> {
+	if chunk_size == 0 {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "chunk size must not be zero"))
+	}
+
+	let mut file = File::open(path)?;
+	let mut chunk = vec![0u8; chunk_size as usize];
+	let mut leaves = Vec::new();
+	let mut file_size = 0u64;
+	loop {
+		let read = read_chunk(&mut file, &mut chunk)?;
+		if read == 0 {
+			break
+		}
+		leaves.push(merkle::hash_leaf(&chunk[..read]));
+		file_size += read as u64;
+		if read < chunk.len() {
+			break
+		}
+	}
+
+	Ok((merkle::root(&leaves), file_size))
+}
+
+// Fills `buf` unless the reader runs out, so short reads don't split a chunk.
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+	let mut filled = 0;
+	while filled < buf.len() {
+		match reader.read(&mut buf[filled..])? {
+			0 => break,
+			read => filled += read,
+		}
+	}
+	Ok(filled)
+}
@@ -1,3 +1,4 @@
 pub mod chain_spec;
+pub mod content_root;
 pub mod rpc;
 pub mod service;
@@ -0,0 +1,31 @@
+//! Runtime API definition for pallet-mydropbox.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_core::H256;
+use sp_std::vec::Vec;
+
+pub use pallet_mydropbox::{FileFilter, License};
+
+sp_api::decl_runtime_apis! {
+	pub trait MydropboxApi<AccountId, Hash, File> where
+		AccountId: Codec,
+		Hash: Codec,
+		File: Codec,
+	{
+		/// Returns up to `limit` files matching `filter` that come after `cursor`, and the cursor of
+		/// the next page, or `None` once every file was examined.
+		fn list_files(filter: FileFilter<AccountId>, cursor: Option<Hash>, limit: u32) -> (Vec<(Hash, File)>, Option<Hash>);
+
+		/// Checks that `chunk` is chunk `index` of `file_id`, given the sibling hashes in `proof`.
+		fn verify_chunk(file_id: Hash, index: u32, chunk: Vec<u8>, proof: Vec<H256>) -> bool;
+
+		/// Returns the key of an encrypted file, encrypted under the public key of `recipient`.
+		fn key_envelope(file_id: Hash, recipient: AccountId) -> Option<Vec<u8>>;
+
+		/// Returns the license `holder` bought for a version of a file, which lets them fetch it
+		/// without paying again.
+		fn license(file_id: Hash, holder: AccountId, version: u32) -> Option<License>;
+	}
+}
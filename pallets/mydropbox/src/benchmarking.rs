@@ -3,18 +3,1003 @@
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::Pallet as Dropbox;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{
+	sp_runtime::traits::{Bounded, One, Saturating, Zero},
+	traits::{Currency, Hooks},
+	weights::Weight,
+	BoundedVec,
+};
+use sp_core::{sr25519, H256};
 use frame_system::RawOrigin;
+use sp_std::{vec, vec::Vec};
+
+const SEED: u32 = 0;
+
+fn file_link(index: u32) -> [u8; 20] {
+	let mut link = [0u8; 20];
+	link[..4].copy_from_slice(&index.to_le_bytes());
+	link
+}
+
+fn content_root(index: u32) -> H256 {
+	H256::from_low_u64_be(index as u64)
+}
+
+// Key the contents of an uploaded file are stored under.
+fn content_id<T: Config>(file_id: &T::Hash) -> H256 {
+	Files::<T>::get(file_id).expect("file was uploaded").content_id()
+}
+
+fn fund<T: Config>(account: &T::AccountId) {
+	T::Currency::make_free_balance_be(account, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+// Sets up a funded accountant to receive rent, keeping the one already set.
+fn set_accountant<T: Config>() {
+	if Accountant::<T>::get().is_none() {
+		let accountant: T::AccountId = account("accountant", 0, SEED);
+		fund::<T>(&accountant);
+		Accountant::<T>::put(accountant);
+	}
+}
+
+fn upload<T: Config>(owner: &T::AccountId, index: u32, file_type: FileType, file_size: u64) -> T::Hash {
+	upload_chunked::<T>(owner, index, file_type, file_size, 1, 1)
+}
+
+fn upload_chunked<T: Config>(
+	owner: &T::AccountId,
+	index: u32,
+	file_type: FileType,
+	file_size: u64,
+	chunk_size: u32,
+	replication_factor: u32,
+) -> T::Hash {
+	// Pays the upload fee, the first rent period and the NFT deposits.
+	fund::<T>(owner);
+	fund::<T>(&Dropbox::<T>::nft_account());
+	set_accountant::<T>();
+	Dropbox::<T>::upload_file(
+		RawOrigin::Signed(owner.clone()).into(),
+		file_link(index),
+		true,
+		file_type,
+		100,
+		file_size,
+		content_root(index),
+		chunk_size,
+		replication_factor,
+	)
+	.expect("owner has room for another file");
+	*FilesPerUser::<T>::get(owner).last().expect("file was just uploaded")
+}
+
+// Stores the most versions, key envelopes and open proposals a file can have. Every version is
+// downloaded by `MaxFilesUploaded` buyers holding a license, who all wait for the key of the
+// current one, and its contents are hosted by `MaxProvidersPerFile` providers.
+fn fill_file<T: Config>(owner: &T::AccountId, index: u32, file_id: &T::Hash) {
+	ExtraQuota::<T>::insert(owner, u64::MAX);
+	let versions = T::MaxVersions::get();
+	for i in 1 ..= versions {
+		// Roots of other files' uploads stay below 2^32.
+		let root = H256::from_low_u64_be(((index as u64 + 1) << 32) | i as u64);
+		Dropbox::<T>::publish_version(RawOrigin::Signed(owner.clone()).into(), *file_id, file_link(index), 100, root, 1)
+			.expect("history has room for the version");
+	}
+
+	let mut downloaders: Vec<T::AccountId> =
+		(0 .. T::MaxFilesUploaded::get()).map(|i| account("downloader", i, SEED)).collect();
+	downloaders.sort();
+	let file = Files::<T>::get(file_id).expect("file was uploaded");
+	for version in FileVersions::<T>::get(file_id).iter().cloned().chain(sp_std::iter::once(file.current_version())) {
+		for downloader in downloaders.iter() {
+			Licenses::<T>::insert(file_id, (downloader.clone(), version.version), License {
+				version: version.version,
+				tier: LicenseTier::Personal,
+				kind: LicenseKind::AllRightsReserved,
+				terms_hash: None,
+				fetches: None,
+			});
+		}
+		let list: BoundedVec<_, _> = downloaders.clone().try_into().expect("downloads fit MaxFilesUploaded");
+		FileDownloads::<T>::insert(file_id, version.version, list);
+
+		let content_id = version.content_id();
+		let hosts: Vec<T::AccountId> = (0 .. T::MaxProvidersPerFile::get())
+			.map(|i| account("host", i, index * (versions + 1) + version.version))
+			.collect();
+		for host in hosts.iter() {
+			let hosted: BoundedVec<_, _> = vec![content_id].try_into().expect("providers host a file");
+			ProviderContents::<T>::insert(host, hosted);
+			Providers::<T>::insert(host, StorageProvider::<T> {
+				stake: Zero::zero(),
+				capacity: u64::MAX,
+				used: 100,
+				price_per_byte: 1,
+				status: ProviderStatus::Active,
+			});
+		}
+		let hosts: BoundedVec<_, _> = hosts.try_into().expect("hosts fit MaxProvidersPerFile");
+		ContentProviders::<T>::insert(content_id, hosts);
+	}
+	for downloader in downloaders.iter() {
+		KeyRequests::<T>::insert(file_id, downloader, KeyRequest::<T> {
+			version: file.version,
+			amount: Zero::zero(),
+			deadline: frame_system::Pallet::<T>::block_number(),
+		});
+	}
+
+	for i in 0 .. T::MaxKeyEnvelopes::get() {
+		let recipient: T::AccountId = account("recipient", i, SEED);
+		KeyEnvelopes::<T>::insert(file_id, &recipient, BoundedVec::<u8, T::MaxEnvelopeLength>::default());
+	}
+	KeyEnvelopeCount::<T>::insert(file_id, T::MaxKeyEnvelopes::get());
+	for proposal_id in 0 .. T::MaxProposals::get() {
+		let proposal = Proposal::<T> { action: FileAction::Delist, approvals: BoundedVec::default() };
+		Proposals::<T>::insert(file_id, proposal_id, proposal);
+	}
+}
+
+fn register<T: Config>(provider: &T::AccountId) {
+	fund::<T>(provider);
+	Dropbox::<T>::register_provider(
+		RawOrigin::Signed(provider.clone()).into(),
+		T::MinProviderStake::get(),
+		u64::MAX,
+		1,
+	)
+	.expect("provider is funded");
+}
+
+// Uploads a file and fills its downloader list with `downloads` accounts, returning the file id
+// and a funded account that has not downloaded it yet.
+fn setup_download<T: Config>(
+	downloads: u32,
+	file_type: FileType,
+	file_size: u64,
+) -> (T::Hash, T::AccountId) {
+	let owner: T::AccountId = account("owner", 0, SEED);
+	set_accountant::<T>();
+
+	let file_id = upload::<T>(&owner, 0, file_type, file_size);
+	let mut downloaders: Vec<T::AccountId> =
+		(0..downloads).map(|i| account("downloader", i, SEED)).collect();
+	downloaders.sort();
+	let downloaders: BoundedVec<_, _> =
+		downloaders.try_into().expect("downloads are below MaxFilesUploaded");
+	FileDownloads::<T>::insert(&file_id, 0, downloaders);
+
+	let caller: T::AccountId = whitelisted_caller();
+	fund::<T>(&caller);
+	(file_id, caller)
+}
+
+// Uploads `files` files for the seller, with the one for sale at the end of its list, and fills the
+// buyer's list up to one file short of the limit.
+fn setup_sale<T: Config>(files: u32) -> (T::Hash, T::AccountId, T::AccountId) {
+	let seller: T::AccountId = account("seller", 0, SEED);
+	let buyer: T::AccountId = whitelisted_caller();
+	for i in 0 .. files - 1 {
+		upload::<T>(&seller, i, FileType::Normal, 100);
+	}
+	let file_id = upload::<T>(&seller, files, FileType::Normal, 100);
+	for i in 0 .. T::MaxFilesUploaded::get() - 1 {
+		upload::<T>(&buyer, files + 1 + i, FileType::Normal, 100);
+	}
+	(file_id, seller, buyer)
+}
+
+/// Hands a file over to `MaxCoOwners` co-owners, the first being the whitelisted caller, who
+/// all have to approve a proposal.
+fn setup_co_owned<T: Config>() -> T::Hash {
+	let owner: T::AccountId = account("owner", 0, SEED);
+	let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+	let mut owners = vec![whitelisted_caller::<T::AccountId>()];
+	for i in 1 .. T::MaxCoOwners::get() {
+		owners.push(account("co-owner", i, SEED));
+	}
+	let threshold = owners.len() as u32;
+	let owners: BoundedVec<_, T::MaxCoOwners> = owners.try_into().expect("co-owners fit the bound");
+	Dropbox::<T>::set_co_owners(RawOrigin::Signed(owner).into(), file_id, owners, threshold)
+		.expect("owner owns the file");
+	file_id
+}
+
+/// Lets `operator` make every operator call for `owner`.
+// Asks the provider to store the file, and has it accept.
+fn assign<T: Config>(owner: &T::AccountId, file_id: T::Hash, provider: &T::AccountId) {
+	Dropbox::<T>::assign_provider(RawOrigin::Signed(owner.clone()).into(), file_id, provider.clone())
+		.expect("provider has room for the file");
+	Dropbox::<T>::fulfill_replication(RawOrigin::Signed(provider.clone()).into(), content_id::<T>(&file_id))
+		.expect("provider was just asked");
+}
+
+fn authorize_operator<T: Config>(owner: &T::AccountId, operator: &T::AccountId) {
+	let scope = OperatorScope { upload: true, update: true, transfer: true };
+	Dropbox::<T>::approve_operator(RawOrigin::Signed(owner.clone()).into(), operator.clone(), scope, None)
+		.expect("operator is another account");
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	add_accountant {
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert_eq!(Accountant::<T>::get(), Some(caller));
+	}
+
+	upload_file {
+		let f in 0 .. T::MaxFilesUploaded::get() - 1;
+		let caller: T::AccountId = whitelisted_caller();
+		for i in 0 .. f {
+			upload::<T>(&caller, i, FileType::Normal, 100);
+		}
+		fund::<T>(&caller);
+		set_accountant::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), file_link(f), true, FileType::Normal, 100, 100, content_root(f), 1, 1)
+	verify {
+		assert_eq!(FilesPerUser::<T>::get(&caller).len() as u32, f + 1);
+	}
+
+	download_file_normal {
+		let d in 0 .. T::MaxFilesUploaded::get() - 1;
+		let (file_id, caller) = setup_download::<T>(d, FileType::Normal, 100);
+	}: download_file(RawOrigin::Signed(caller.clone()), file_id, None, LicenseTier::Personal)
+	verify {
+		assert!(FileDownloads::<T>::get(&file_id, 0).contains(&caller));
+	}
+
+	// The caller holds a personal license and buys the commercial one.
+	upgrade_license {
+		let d in 1 .. T::MaxFilesUploaded::get() - 1;
+		let (file_id, caller) = setup_download::<T>(d - 1, FileType::Normal, 100);
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let license = LicenseTerms { kind: LicenseKind::Cc0, terms_hash: None, commercial_cost: Some(500) };
+		Dropbox::<T>::set_license(RawOrigin::Signed(owner).into(), file_id, license)
+			.expect("owner owns the file");
+		Dropbox::<T>::download_file(RawOrigin::Signed(caller.clone()).into(), file_id, None, LicenseTier::Personal)
+			.expect("caller is funded");
+	}: download_file(RawOrigin::Signed(caller.clone()), file_id, None, LicenseTier::Commercial)
+	verify {
+		let license = Licenses::<T>::get(&file_id, (caller, 0)).expect("caller bought a license");
+		assert_eq!(license.tier, LicenseTier::Commercial);
+	}
+
+	download_file_normal_oversized {
+		let d in 0 .. T::MaxFilesUploaded::get() - 1;
+		let file_size = T::FileSizeLimit::get().max(250) + 1_000;
+		let (file_id, caller) = setup_download::<T>(d, FileType::Normal, file_size);
+	}: download_file(RawOrigin::Signed(caller.clone()), file_id, None, LicenseTier::Personal)
+	verify {
+		assert!(FileDownloads::<T>::get(&file_id, 0).contains(&caller));
+	}
+
+	download_file_privileged {
+		let d in 0 .. T::MaxFilesUploaded::get() - 1;
+		let file_size = T::FileSizeLimit::get().max(250) + 1_000;
+		let (file_id, caller) = setup_download::<T>(d, FileType::Privileged, file_size);
+	}: download_file(RawOrigin::Signed(caller.clone()), file_id, None, LicenseTier::Personal)
+	verify {
+		assert!(FileDownloads::<T>::get(&file_id, 0).contains(&caller));
+	}
+
+	fetch_file {
+		let (file_id, caller) = setup_download::<T>(0, FileType::Normal, 100);
+		Dropbox::<T>::download_file(RawOrigin::Signed(caller.clone()).into(), file_id, None, LicenseTier::Personal)?;
+	}: _(RawOrigin::Signed(caller.clone()), file_id, None)
+	verify {
+		assert!(Licenses::<T>::contains_key(&file_id, (caller, 0)));
+	}
+
+	list_file {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let price = T::Currency::minimum_balance();
+	}: _(RawOrigin::Signed(caller), file_id, price)
+	verify {
+		assert_eq!(Listings::<T>::get(&file_id).map(|listing| listing.price), Some(price));
+	}
+
+	delist_file {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		Dropbox::<T>::list_file(RawOrigin::Signed(caller.clone()).into(), file_id, T::Currency::minimum_balance())
+			.expect("caller owns the file");
+	}: _(RawOrigin::Signed(caller), file_id)
+	verify {
+		assert!(!Listings::<T>::contains_key(&file_id));
+	}
+
+	buy_file {
+		let f in 1 .. T::MaxFilesUploaded::get();
+		let (file_id, seller, buyer) = setup_sale::<T>(f);
+		let price = T::Currency::minimum_balance();
+		Dropbox::<T>::list_file(RawOrigin::Signed(seller).into(), file_id, price).expect("seller owns the file");
+	}: _(RawOrigin::Signed(buyer.clone()), file_id, price)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.owner), Some(buyer));
+	}
+
+	// The offers are full, so the lowest one is refunded.
+	make_offer {
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		for i in 0 .. T::MaxOffersPerFile::get() {
+			let bidder: T::AccountId = account("bidder", i, SEED);
+			fund::<T>(&bidder);
+			Dropbox::<T>::make_offer(RawOrigin::Signed(bidder).into(), file_id, T::Currency::minimum_balance())
+				.expect("bidder is funded");
+		}
+		fund::<T>(&caller);
+		let amount = T::Currency::minimum_balance() * 2u32.into();
+	}: _(RawOrigin::Signed(caller.clone()), file_id, amount)
+	verify {
+		assert!(Offers::<T>::get(&file_id).contains(&(caller, amount)));
+	}
+
+	withdraw_offer {
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		fund::<T>(&caller);
+		Dropbox::<T>::make_offer(RawOrigin::Signed(caller.clone()).into(), file_id, T::Currency::minimum_balance())
+			.expect("caller is funded");
+	}: _(RawOrigin::Signed(caller), file_id)
+	verify {
+		assert!(Offers::<T>::get(&file_id).is_empty());
+	}
+
+	accept_offer {
+		let f in 1 .. T::MaxFilesUploaded::get();
+		let (file_id, seller, buyer) = setup_sale::<T>(f);
+		Dropbox::<T>::list_file(RawOrigin::Signed(seller.clone()).into(), file_id, T::Currency::minimum_balance())
+			.expect("seller owns the file");
+		Dropbox::<T>::make_offer(RawOrigin::Signed(buyer.clone()).into(), file_id, T::Currency::minimum_balance())
+			.expect("buyer is funded");
+	}: _(RawOrigin::Signed(seller), file_id, buyer.clone())
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.owner), Some(buyer));
+	}
+
+	update_file {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+	}: _(RawOrigin::Signed(caller), file_id, 200, false)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.cost), Some(200));
+	}
+
+	set_license {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let license = LicenseTerms { kind: LicenseKind::Custom, terms_hash: Some(H256::repeat_byte(1)), commercial_cost: Some(500) };
+	}: _(RawOrigin::Signed(caller), file_id, license)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.license), Some(license));
+	}
+
+	set_co_owners {
+		let f in 1 .. T::MaxFilesUploaded::get();
+		let (file_id, seller, _) = setup_sale::<T>(f);
+		let owners: BoundedVec<_, T::MaxCoOwners> = (0 .. T::MaxCoOwners::get())
+			.map(|i| account("co-owner", i, SEED))
+			.collect::<Vec<_>>()
+			.try_into()
+			.expect("co-owners fit the bound");
+	}: _(RawOrigin::Signed(seller), file_id, owners, T::MaxCoOwners::get())
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.owner), Some(Dropbox::<T>::co_owner_account(&file_id)));
+	}
+
+	// The proposal needs more approvals, so it is only stored.
+	propose {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = setup_co_owned::<T>();
+	}: _(RawOrigin::Signed(caller), file_id, FileAction::Update(200, false))
+	verify {
+		assert!(Proposals::<T>::contains_key(&file_id, 0));
+	}
+
+	approve {
+		let caller: T::AccountId = account("co-owner", 1, SEED);
+		let file_id = setup_co_owned::<T>();
+		Dropbox::<T>::propose(RawOrigin::Signed(whitelisted_caller()).into(), file_id, FileAction::Update(200, false))
+			.expect("proposer is a co-owner");
+	}: _(RawOrigin::Signed(caller.clone()), file_id, 0)
+	verify {
+		let approved = Proposals::<T>::get(&file_id, 0).map_or(true, |proposal| proposal.approvals.contains(&caller));
+		assert!(approved);
+	}
+
+	cancel_proposal {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = setup_co_owned::<T>();
+		Dropbox::<T>::propose(RawOrigin::Signed(caller.clone()).into(), file_id, FileAction::Update(200, false))
+			.expect("proposer is a co-owner");
+	}: _(RawOrigin::Signed(caller), file_id, 0)
+	verify {
+		assert!(!Proposals::<T>::contains_key(&file_id, 0));
+	}
+
+	approve_operator {
+		let caller: T::AccountId = whitelisted_caller();
+		let operator: T::AccountId = account("operator", 0, SEED);
+		let scope = OperatorScope { upload: true, update: true, transfer: true };
+		let expires_at = frame_system::Pallet::<T>::block_number().saturating_add(10u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), operator.clone(), scope, Some(expires_at))
+	verify {
+		assert!(Operators::<T>::contains_key(&caller, &operator));
+	}
+
+	revoke_operator {
+		let caller: T::AccountId = whitelisted_caller();
+		let operator: T::AccountId = account("operator", 0, SEED);
+		authorize_operator::<T>(&caller, &operator);
+	}: _(RawOrigin::Signed(caller.clone()), operator.clone())
+	verify {
+		assert!(!Operators::<T>::contains_key(&caller, &operator));
+	}
+
+	upload_file_for {
+		let f in 0 .. T::MaxFilesUploaded::get() - 1;
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		for i in 0 .. f {
+			upload::<T>(&owner, i, FileType::Normal, 100);
+		}
+		fund::<T>(&owner);
+		set_accountant::<T>();
+		authorize_operator::<T>(&owner, &caller);
+	}: _(RawOrigin::Signed(caller), owner.clone(), file_link(f), true, FileType::Normal, 100, 100, content_root(f), 1, 1)
+	verify {
+		assert_eq!(FilesPerUser::<T>::get(&owner).len() as u32, f + 1);
+	}
+
+	update_file_for {
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		authorize_operator::<T>(&owner, &caller);
+	}: _(RawOrigin::Signed(caller), owner, file_id, 200, false)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.cost), Some(200));
+	}
+
+	transfer_file_for {
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		let operator: T::AccountId = account("operator", 0, SEED);
+		authorize_operator::<T>(&owner, &operator);
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+	}: _(RawOrigin::Signed(operator), owner, file_id, recipient.clone())
+	verify {
+		assert_eq!(PendingTransfers::<T>::get(&file_id).map(|pending| pending.to), Some(recipient));
+	}
+
+	transfer_file {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+	}: _(RawOrigin::Signed(caller), file_id, recipient.clone())
+	verify {
+		assert_eq!(PendingTransfers::<T>::get(&file_id).map(|pending| pending.to), Some(recipient));
+	}
+
+	accept_transfer {
+		let f in 1 .. T::MaxFilesUploaded::get();
+		let (file_id, seller, buyer) = setup_sale::<T>(f);
+		Dropbox::<T>::transfer_file(RawOrigin::Signed(seller).into(), file_id, buyer.clone())
+			.expect("seller owns the file");
+	}: _(RawOrigin::Signed(buyer.clone()), file_id)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.owner), Some(buyer));
+	}
+
+	reject_transfer {
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		Dropbox::<T>::transfer_file(RawOrigin::Signed(owner).into(), file_id, caller.clone())
+			.expect("owner owns the file");
+	}: _(RawOrigin::Signed(caller), file_id)
+	verify {
+		assert!(!PendingTransfers::<T>::contains_key(&file_id));
+	}
+
+	expire_transfer_offers {
+		let t in 0 .. T::MaxTransferOffersPerBlock::get();
+		for i in 0 .. t {
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Normal, 100);
+			let recipient: T::AccountId = account("recipient", i, SEED);
+			Dropbox::<T>::transfer_file(RawOrigin::Signed(owner).into(), file_id, recipient)
+				.expect("owner owns the file");
+		}
+		let now = frame_system::Pallet::<T>::block_number()
+			.saturating_add(T::TransferOfferTimeout::get())
+			.saturating_add(One::one());
+	}: {
+		Dropbox::<T>::expire_transfer_offers(now);
+	}
+	verify {
+		assert_eq!(PendingTransfers::<T>::iter().count(), 0);
+	}
+
+	// The file is listed, so it is taken off sale.
+	create_auction {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		Dropbox::<T>::list_file(RawOrigin::Signed(caller.clone()).into(), file_id, T::Currency::minimum_balance())
+			.expect("caller owns the file");
+		let end = frame_system::Pallet::<T>::block_number().saturating_add(T::MaxAuctionDuration::get());
+	}: _(RawOrigin::Signed(caller), file_id, T::Currency::minimum_balance(), end)
+	verify {
+		assert!(Auctions::<T>::contains_key(&file_id));
+	}
+
+	// The highest bid is refunded.
+	bid {
+		let caller: T::AccountId = whitelisted_caller();
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		let end = frame_system::Pallet::<T>::block_number().saturating_add(T::MaxAuctionDuration::get());
+		Dropbox::<T>::create_auction(RawOrigin::Signed(owner).into(), file_id, T::Currency::minimum_balance(), end)
+			.expect("owner owns the file");
+		let bidder: T::AccountId = account("bidder", 0, SEED);
+		fund::<T>(&bidder);
+		Dropbox::<T>::bid(RawOrigin::Signed(bidder).into(), file_id, T::Currency::minimum_balance())
+			.expect("bidder is funded");
+		fund::<T>(&caller);
+		let amount = T::Currency::minimum_balance() * 2u32.into();
+	}: _(RawOrigin::Signed(caller.clone()), file_id, amount)
+	verify {
+		assert_eq!(Auctions::<T>::get(&file_id).and_then(|auction| auction.bid), Some((caller, amount)));
+	}
+
+	cancel_auction {
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let end = frame_system::Pallet::<T>::block_number().saturating_add(T::MaxAuctionDuration::get());
+		Dropbox::<T>::create_auction(RawOrigin::Signed(caller.clone()).into(), file_id, T::Currency::minimum_balance(), end)
+			.expect("caller owns the file");
+	}: _(RawOrigin::Signed(caller), file_id)
+	verify {
+		assert!(!Auctions::<T>::contains_key(&file_id));
+	}
+
+	settle_auctions {
+		let a in 0 .. T::MaxAuctionsPerBlock::get();
+		let end = frame_system::Pallet::<T>::block_number().saturating_add(One::one());
+		let mut file_ids = Vec::new();
+		for i in 0 .. a {
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Normal, 100);
+			Dropbox::<T>::create_auction(RawOrigin::Signed(owner).into(), file_id, T::Currency::minimum_balance(), end)
+				.expect("owner owns the file");
+			let bidder: T::AccountId = account("bidder", i, SEED);
+			fund::<T>(&bidder);
+			Dropbox::<T>::bid(RawOrigin::Signed(bidder).into(), file_id, T::Currency::minimum_balance())
+				.expect("bidder is funded");
+			file_ids.push(file_id);
+		}
+	}: {
+		Dropbox::<T>::settle_auctions(end);
+	}
+	verify {
+		for (i, file_id) in file_ids.iter().enumerate() {
+			let bidder: T::AccountId = account("bidder", i as u32, SEED);
+			assert_eq!(Files::<T>::get(file_id).map(|file| file.owner), Some(bidder));
+		}
+	}
+
+	register_provider {
+		let caller: T::AccountId = whitelisted_caller();
+		fund::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), T::MinProviderStake::get(), 1_000_000, 1)
+	verify {
+		assert!(Providers::<T>::contains_key(&caller));
+	}
+
+	update_provider {
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+	}: _(RawOrigin::Signed(caller.clone()), 1_000_000, 2)
+	verify {
+		assert_eq!(Providers::<T>::get(&caller).map(|provider| provider.price_per_byte), Some(2));
+	}
+
+	deregister_provider {
+		let f in 0 .. T::MaxFilesPerProvider::get();
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+		for i in 0 .. f {
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Normal, 100);
+			assign::<T>(&owner, file_id, &caller);
+		}
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(ProviderContents::<T>::get(&caller).is_empty());
+	}
+
+	withdraw_stake {
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+		Dropbox::<T>::deregister_provider(RawOrigin::Signed(caller.clone()).into())
+			.expect("provider is active");
+		let now = frame_system::Pallet::<T>::block_number();
+		frame_system::Pallet::<T>::set_block_number(now + T::UnbondingPeriod::get());
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!Providers::<T>::contains_key(&caller));
+	}
+
+	assign_provider {
+		let provider: T::AccountId = account("provider", 0, SEED);
+		register::<T>(&provider);
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+	}: _(RawOrigin::Signed(caller), file_id, provider.clone())
+	verify {
+		assert!(ReplicationOrders::<T>::get(&content_id::<T>(&file_id)).iter().any(|order| order.provider == provider));
+	}
+
+	unassign_provider {
+		let provider: T::AccountId = account("provider", 0, SEED);
+		register::<T>(&provider);
+		let caller: T::AccountId = whitelisted_caller();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		assign::<T>(&caller, file_id, &provider);
+	}: _(RawOrigin::Signed(caller), file_id, provider.clone())
+	verify {
+		assert!(!ContentProviders::<T>::get(&content_id::<T>(&file_id)).contains(&provider));
+	}
+
+	// The history is full, so the oldest version is dropped along with the most downloaders it
+	// can have, each with a license and a pending key request.
+	publish_version {
+		let caller: T::AccountId = whitelisted_caller();
+		ExtraQuota::<T>::insert(&caller, u64::MAX);
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let mut downloaders: Vec<T::AccountId> =
+			(0..T::MaxFilesUploaded::get()).map(|i| account("downloader", i, SEED)).collect();
+		downloaders.sort();
+		for downloader in downloaders.iter() {
+			Licenses::<T>::insert(&file_id, (downloader.clone(), 0), License {
+				version: 0,
+				tier: LicenseTier::Personal,
+				kind: LicenseKind::AllRightsReserved,
+				terms_hash: None,
+				fetches: None,
+			});
+			KeyRequests::<T>::insert(&file_id, downloader, KeyRequest::<T> {
+				version: 0,
+				amount: Zero::zero(),
+				deadline: frame_system::Pallet::<T>::block_number(),
+			});
+		}
+		let downloaders: BoundedVec<_, _> = downloaders.try_into().expect("downloads fit MaxFilesUploaded");
+		FileDownloads::<T>::insert(&file_id, 0, downloaders);
+		let versions = T::MaxVersions::get();
+		for i in 1 ..= versions {
+			Dropbox::<T>::publish_version(
+				RawOrigin::Signed(caller.clone()).into(),
+				file_id,
+				file_link(i),
+				100,
+				content_root(i),
+				1,
+			)
+			.expect("history has room for the version");
+		}
+	}: _(RawOrigin::Signed(caller), file_id, file_link(versions + 1), 100, content_root(versions + 1), 1)
+	verify {
+		assert_eq!(FileVersions::<T>::get(&file_id).len() as u32, versions);
+		assert!(FileDownloads::<T>::get(&file_id, 0).is_empty());
+	}
+
+	// The history is full, and the oldest version is restored.
+	rollback_to_version {
+		let caller: T::AccountId = whitelisted_caller();
+		ExtraQuota::<T>::insert(&caller, u64::MAX);
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		for i in 1 ..= T::MaxVersions::get() {
+			Dropbox::<T>::publish_version(
+				RawOrigin::Signed(caller.clone()).into(),
+				file_id,
+				file_link(i),
+				100,
+				content_root(i),
+				1,
+			)
+			.expect("history has room for the version");
+		}
+	}: _(RawOrigin::Signed(caller), file_id, 0)
+	verify {
+		assert_eq!(Files::<T>::get(&file_id).map(|file| file.version), Some(0));
+	}
+
+	// The recipient bought the file, so granting access also settles the key request.
+	grant_access {
+		let (file_id, recipient) = setup_download::<T>(0, FileType::Encrypted, 100);
+		Dropbox::<T>::download_file(RawOrigin::Signed(recipient.clone()).into(), file_id, None, LicenseTier::Personal)
+			.expect("recipient is funded");
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let envelope: BoundedVec<u8, T::MaxEnvelopeLength> =
+			vec![1u8; T::MaxEnvelopeLength::get() as usize].try_into().expect("envelope fits the bound");
+	}: _(RawOrigin::Signed(owner), file_id, recipient.clone(), envelope)
+	verify {
+		assert!(KeyEnvelopes::<T>::contains_key(&file_id, &recipient));
+		assert!(!KeyRequests::<T>::contains_key(&file_id, &recipient));
+	}
+
+	revoke_access {
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let file_id = upload::<T>(&caller, 0, FileType::Encrypted, 100);
+		let envelope: BoundedVec<u8, T::MaxEnvelopeLength> =
+			vec![1u8; T::MaxEnvelopeLength::get() as usize].try_into().expect("envelope fits the bound");
+		KeyEnvelopes::<T>::insert(&file_id, &recipient, envelope);
+	}: _(RawOrigin::Signed(caller), file_id, recipient.clone())
+	verify {
+		assert!(!KeyEnvelopes::<T>::contains_key(&file_id, &recipient));
+	}
+
+	download_file_encrypted {
+		let d in 0 .. T::MaxFilesUploaded::get() - 1;
+		let (file_id, caller) = setup_download::<T>(d, FileType::Encrypted, 100);
+	}: download_file(RawOrigin::Signed(caller.clone()), file_id, None, LicenseTier::Personal)
+	verify {
+		assert!(KeyRequests::<T>::contains_key(&file_id, &caller));
+	}
+
+	// Every request looked at is still open and gets refunded.
+	expire_key_requests {
+		let r in 0 .. T::MaxKeyRequestsPerBlock::get();
+		set_accountant::<T>();
+		for i in 0 .. r {
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Encrypted, 100);
+			let buyer: T::AccountId = account("buyer", i, SEED);
+			fund::<T>(&buyer);
+			Dropbox::<T>::download_file(RawOrigin::Signed(buyer).into(), file_id, None, LicenseTier::Personal)
+				.expect("buyer is funded");
+		}
+		let now = frame_system::Pallet::<T>::block_number()
+			.saturating_add(T::KeyRequestTimeout::get())
+			.saturating_add(One::one());
+	}: {
+		Dropbox::<T>::expire_key_requests(now);
+	}
+	verify {
+		assert_eq!(KeyRequests::<T>::iter().count(), 0);
+	}
+
+	set_delivery_key {
+		let caller: T::AccountId = whitelisted_caller();
+		let key = sr25519::Public::from_raw([1u8; 32]);
+	}: _(RawOrigin::Signed(caller.clone()), key)
+	verify {
+		assert_eq!(DeliveryKeys::<T>::get(&caller), Some(key));
+	}
+
+	fulfill_key_request {
+		let (file_id, buyer) = setup_download::<T>(0, FileType::Encrypted, 100);
+		Dropbox::<T>::download_file(RawOrigin::Signed(buyer.clone()).into(), file_id, None, LicenseTier::Personal)
+			.expect("buyer is funded");
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+		DeliveryKeys::<T>::insert(&owner, key);
+		let envelope: BoundedVec<u8, T::MaxEnvelopeLength> =
+			vec![1u8; T::MaxEnvelopeLength::get() as usize].try_into().expect("envelope fits the bound");
+		let payload = Dropbox::<T>::key_delivery_payload(&file_id, &buyer, &envelope);
+		let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &key, &payload).expect("key was just generated");
+	}: _(RawOrigin::None, file_id, buyer.clone(), envelope, signature)
+	verify {
+		assert!(KeyEnvelopes::<T>::contains_key(&file_id, &buyer));
+	}
+
+	set_reporter {
+		let key = sr25519::Public::from_raw([1u8; 32]);
+	}: _(RawOrigin::Root, key, true)
+	verify {
+		assert!(AvailabilityReporters::<T>::contains_key(&key));
+	}
+
+	set_plan {
+		let plan = QuotaPlan { bytes: 1_000_000, price: T::Currency::minimum_balance() };
+	}: _(RawOrigin::Root, 0, Some(plan))
+	verify {
+		assert_eq!(QuotaPlans::<T>::get(0), Some(plan));
+	}
+
+	buy_plan {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		fund::<T>(&caller);
+		set_accountant::<T>();
+		let plan = QuotaPlan { bytes: 1_000_000, price: T::Currency::minimum_balance() };
+		QuotaPlans::<T>::insert(0, plan);
+	}: _(RawOrigin::Signed(caller.clone()), 0)
+	verify {
+		assert_eq!(ExtraQuota::<T>::get(&caller), 1_000_000);
+	}
+
+	extend_storage {
+		let caller: T::AccountId = whitelisted_caller();
+		fund::<T>(&caller);
+		set_accountant::<T>();
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let now = frame_system::Pallet::<T>::block_number();
+		FileRent::<T>::insert(&file_id, RentStatus { paid_through: now, expired: false });
+		let blocks: T::BlockNumber = 100u32.into();
+	}: _(RawOrigin::Signed(caller), file_id, blocks)
+	verify {
+		assert_eq!(FileRent::<T>::get(&file_id).map(|rent| rent.paid_through), Some(now + blocks));
+	}
+
+	// Every file checked is past its grace period and gets removed, along with as many key
+	// envelopes and proposals as it can have.
+	collect_expired {
+		let f in 1 .. 20;
+		for i in 0 .. f {
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Normal, 100);
+			fill_file::<T>(&owner, i, &file_id);
+			FileRent::<T>::insert(&file_id, RentStatus { paid_through: 0u32.into(), expired: true });
+		}
+		let now = T::RentGracePeriod::get().saturating_add(2u32.into());
+	}: {
+		Dropbox::<T>::on_idle(now, Weight::MAX);
+	}
+	verify {
+		assert_eq!(FileRent::<T>::iter().count(), 0);
+		assert_eq!(KeyEnvelopes::<T>::iter().count(), 0);
+		assert_eq!(Licenses::<T>::iter().count(), 0);
+	}
+
+	fund_file {
+		let caller: T::AccountId = whitelisted_caller();
+		fund::<T>(&caller);
+		let file_id = upload::<T>(&caller, 0, FileType::Normal, 100);
+		let fee = StorageFunds::<T>::get(&content_id::<T>(&file_id));
+		let amount = T::Currency::minimum_balance().saturating_mul(100u32.into());
+	}: _(RawOrigin::Signed(caller), file_id, amount)
+	verify {
+		assert_eq!(StorageFunds::<T>::get(&content_id::<T>(&file_id)), fee.saturating_add(amount));
+	}
+
+	// Every candidate is eligible, so orders are opened up to the replication factor.
+	replicate {
+		for i in 0 .. T::MaxReplicationCandidates::get() {
+			register::<T>(&account("provider", i, SEED));
+		}
+		let owner: T::AccountId = account("owner", 0, SEED);
+		fund::<T>(&owner);
+		let file_id = upload_chunked::<T>(&owner, 0, FileType::Normal, 100, 1, T::MaxProvidersPerFile::get());
+		let amount = T::Currency::minimum_balance().saturating_add(100_000u32.into());
+		Dropbox::<T>::fund_file(RawOrigin::Signed(owner.clone()).into(), file_id, amount)
+			.expect("owner is funded");
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller), file_id)
+	verify {
+		let expected = T::MaxProvidersPerFile::get().min(T::MaxReplicationCandidates::get());
+		assert_eq!(ReplicationOrders::<T>::get(&content_id::<T>(&file_id)).len() as u32, expected);
+	}
+
+	fulfill_replication {
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+		let owner: T::AccountId = account("owner", 0, SEED);
+		fund::<T>(&owner);
+		let file_id = upload::<T>(&owner, 0, FileType::Normal, 100);
+		let reward: BalanceOf<T> = 100u32.into();
+		Dropbox::<T>::fund_file(
+			RawOrigin::Signed(owner).into(),
+			file_id,
+			T::Currency::minimum_balance().saturating_add(reward),
+		)
+		.expect("owner is funded");
+		let order = ReplicationOrder::<T> {
+			provider: caller.clone(),
+			reward,
+			expires_at: frame_system::Pallet::<T>::block_number(),
+		};
+		let content_id = content_id::<T>(&file_id);
+		ReplicationOrders::<T>::insert(&content_id, BoundedVec::try_from(vec![order]).expect("one order fits"));
+	}: _(RawOrigin::Signed(caller.clone()), content_id)
+	verify {
+		assert!(ContentProviders::<T>::get(&content_id).contains(&caller));
+	}
+
+	// A wrong chunk with a full-length proof, so verification runs to the end and the provider is
+	// slashed.
+	respond_to_challenge {
+		let c in 1 .. T::MaxChunkSize::get();
+		let caller: T::AccountId = whitelisted_caller();
+		register::<T>(&caller);
+		let owner: T::AccountId = account("owner", 0, SEED);
+		let proof_length = T::MaxProofLength::get().min(31);
+		let chunk_size = T::MaxChunkSize::get();
+		// The file is far larger than any default quota.
+		ExtraQuota::<T>::insert(&owner, u64::MAX);
+		let file_id = upload_chunked::<T>(
+			&owner,
+			0,
+			FileType::Normal,
+			(chunk_size as u64) << proof_length,
+			chunk_size,
+			1,
+		);
+		Challenges::<T>::insert(&caller, Challenge::<T> {
+			content_id: content_id::<T>(&file_id),
+			chunk_index: 0,
+			deadline: frame_system::Pallet::<T>::block_number(),
+		});
+		let chunk: BoundedVec<_, _> = vec![0u8; c as usize].try_into().expect("c is below MaxChunkSize");
+		let proof: BoundedVec<_, _> =
+			vec![H256::zero(); proof_length as usize].try_into().expect("proof fits MaxProofLength");
+	}: _(RawOrigin::Signed(caller.clone()), chunk, proof)
+	verify {
+		assert!(!Challenges::<T>::contains_key(&caller));
+	}
+
+	on_initialize {
+		let e in 0 .. T::MaxChallengesPerBlock::get();
+		let c in 0 .. T::MaxChallengesPerBlock::get();
+		let now = T::ChallengePeriod::get() * 2u32.into();
+
+		for i in 0 .. c {
+			let provider: T::AccountId = account("provider", i, SEED);
+			register::<T>(&provider);
+			let owner: T::AccountId = account("owner", i, SEED);
+			let file_id = upload::<T>(&owner, i, FileType::Normal, 100);
+			assign::<T>(&owner, file_id, &provider);
+		}
+
+		let mut lapsed = Vec::new();
+		for i in 0 .. e {
+			let provider: T::AccountId = account("lapsed", i, SEED);
+			register::<T>(&provider);
+			Challenges::<T>::insert(&provider, Challenge::<T> {
+				content_id: Default::default(),
+				chunk_index: 0,
+				deadline: now.saturating_sub(One::one()),
+			});
+			lapsed.push(provider);
+		}
+		let lapsed: BoundedVec<_, _> = lapsed.try_into().expect("e is below MaxChallengesPerBlock");
+		ChallengeDeadlines::<T>::insert(now, lapsed);
+	}: {
+		Dropbox::<T>::on_initialize(now);
+	}
+	verify {
+		assert_eq!(Challenges::<T>::iter().count() as u32, c);
+	}
+
+	report_unavailable {
+		let f in 1 .. T::MaxAvailabilityChecks::get();
+		let mut files = Vec::new();
+		for i in 0 .. f {
+			let owner: T::AccountId = account("owner", i, SEED);
+			files.push(upload::<T>(&owner, i, FileType::Normal, 100));
+		}
+		let files: BoundedVec<_, _> = files.try_into().expect("f is below MaxAvailabilityChecks");
+		let block_number = frame_system::Pallet::<T>::block_number();
+		let reporter = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+		AvailabilityReporters::<T>::insert(&reporter, ());
+		let payload = Dropbox::<T>::availability_report_payload(&block_number, &files);
+		let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &reporter, &payload).expect("key was just generated");
+	}: _(RawOrigin::None, block_number, files, reporter, signature)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(
+			NextAvailabilityReport::<T>::get(),
+			block_number.saturating_add(T::AvailabilityCheckInterval::get())
+		);
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(Dropbox, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -2,22 +2,64 @@
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod merkle;
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
 #[frame_support::pallet]
 pub mod pallet {
 
+	use crate::{merkle, weights::WeightInfo};
 	use frame_support::{
-		dispatch::{DispatchResult, DispatchResultWithPostInfo},
+		dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo},
 		pallet_prelude::*,
-		sp_runtime::{traits::{Hash, Zero}, SaturatedConversion},
-		traits::{Currency, ExistenceRequirement, Randomness},
-		transactional,
+		sp_runtime::{
+			offchain::{http, Duration, StorageKind},
+			traits::{AccountIdConversion, Hash, One, Saturating, TrailingZeroInput, Zero},
+			Perbill, SaturatedConversion,
+		},
+		traits::{
+			tokens::{nonfungibles, Locker}, BalanceStatus, Currency, EnsureOriginWithArg, ExistenceRequirement, Randomness,
+			ReservableCurrency,
+		},
+		transactional, PalletId,
 	};
+	use frame_support::sp_std::vec::Vec;
+	#[cfg(any(feature = "try-runtime", test))]
+	use frame_support::sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
 	use scale_info::prelude::string::String;
+	use sp_core::{crypto::KeyTypeId, sr25519, H256};
+
+    use frame_system::{
+		offchain::{SendTransactionTypes, SubmitTransaction},
+		pallet_prelude::*,
+	};
+
+	// How long the offchain worker waits for the gateway and the key service to answer.
+	const HTTP_TIMEOUT_MS: u64 = 5_000;
 
-    use frame_system::pallet_prelude::*;
+	/// Key type of the delivery keys file owners keep in their node's keystore.
+	pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"mdbx");
+
+	/// Persistent offchain storage key holding the URL of the HTTP service the node's offchain
+	/// worker asks for file keys encrypted for a buyer. Set it with the `offchain_localStorageSet`
+	/// RPC.
+	pub const KEY_SERVICE_STORAGE_KEY: &[u8] = b"mydropbox::key-service";
 
 	type AccountOf<T> = <T as frame_system::Config>::AccountId;
-	type BalanceOf<T> =
+	pub type CollectionIdOf<T> =
+		<<T as Config>::Nfts as nonfungibles::Inspect<<T as frame_system::Config>::AccountId>>::CollectionId;
+	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 
@@ -25,6 +67,47 @@ pub mod pallet {
 	pub enum FileType {
 		Normal,
 		Privileged,
+		/// The contents are encrypted, and recipients get the file key through key envelopes.
+		Encrypted,
+	}
+
+	/// License a file is offered under, named after its SPDX identifier.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum LicenseKind {
+		AllRightsReserved,
+		Cc0,
+		CcBy,
+		CcBySa,
+		CcByNc,
+		Mit,
+		Apache2,
+		/// Terms of the owner's own, identified by the terms hash.
+		Custom,
+	}
+
+	impl Default for LicenseKind {
+		fn default() -> Self {
+			LicenseKind::AllRightsReserved
+		}
+	}
+
+	/// Terms buyers of a file agree to.
+	#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct LicenseTerms {
+		pub kind: LicenseKind,
+		/// Hash of the full terms, required for custom licenses.
+		pub terms_hash: Option<H256>,
+		/// Download cost for commercial use, or `None` if the file is only sold for personal use.
+		pub commercial_cost: Option<u64>,
+	}
+
+	/// Use a buyer pays for when downloading a file.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum LicenseTier {
+		/// Bought at the download cost of the file.
+		Personal,
+		/// Bought at the commercial cost of the license terms.
+		Commercial,
 	}
 
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -37,17 +120,347 @@ pub mod pallet {
 		pub cost: u64,
 		pub file_size: u64,
 		pub owner: AccountOf<T>,
+		/// Merkle root over the `chunk_size`-byte chunks of the file contents.
+		pub content_root: H256,
+		pub chunk_size: u32,
+		/// Number of providers that should hold the file.
+		pub replication_factor: u32,
+		/// Version the file currently serves.
+		pub version: u32,
+		pub license: LicenseTerms,
+	}
+
+	impl<T: Config> File<T> {
+		/// A file about to be uploaded by `owner`.
+		pub fn new(owner: AccountOf<T>, upload: FileUpload) -> Self {
+			File {
+				file_link: upload.file_link,
+				allow_download: upload.allow_download,
+				file_type: upload.file_type,
+				cost: upload.cost,
+				file_size: upload.file_size,
+				owner,
+				content_root: upload.content_root,
+				chunk_size: upload.chunk_size,
+				replication_factor: upload.replication_factor,
+				version: 0,
+				license: LicenseTerms::default(),
+			}
+		}
+
+		pub fn current_version(&self) -> FileVersion {
+			FileVersion {
+				version: self.version,
+				file_link: self.file_link,
+				file_size: self.file_size,
+				content_root: self.content_root,
+				chunk_size: self.chunk_size,
+			}
+		}
+
+		/// The file as it was at `version`.
+		pub fn with_version(self, version: FileVersion) -> Self {
+			File {
+				file_link: version.file_link,
+				file_size: version.file_size,
+				content_root: version.content_root,
+				chunk_size: version.chunk_size,
+				version: version.version,
+				..self
+			}
+		}
+
+		/// Key the contents of the file are stored under.
+		pub fn content_id(&self) -> H256 {
+			merkle::content_id(&self.content_root, self.file_size, self.chunk_size)
+		}
+
+		/// Number of chunks the file is split into.
+		pub fn chunk_count(&self) -> u32 {
+			merkle::chunk_count(self.file_size, self.chunk_size)
+		}
+
+		/// Checks that `chunk` is chunk `index` of the file, given the sibling hashes in `proof`.
+		pub fn verify_chunk(&self, index: u32, chunk: &[u8], proof: &[H256]) -> bool {
+			chunk.len() <= self.chunk_size as usize &&
+				merkle::verify(
+					&self.content_root,
+					merkle::hash_leaf(chunk),
+					index,
+					self.chunk_count(),
+					proof,
+				)
+		}
+	}
+
+	/// Parameters of a file uploaded with `upload_files`, as taken by `upload_file`.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct FileUpload {
+		pub file_link: [u8; 20],
+		pub allow_download: bool,
+		pub file_type: FileType,
+		pub cost: u64,
+		pub file_size: u64,
+		pub content_root: H256,
+		pub chunk_size: u32,
+		pub replication_factor: u32,
+	}
+
+	/// The link and contents of a file at one of its versions.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct FileVersion {
+		pub version: u32,
+		pub file_link: [u8; 20],
+		pub file_size: u64,
+		pub content_root: H256,
+		pub chunk_size: u32,
+	}
+
+	impl FileVersion {
+		/// Key the contents of the version are stored under.
+		pub fn content_id(&self) -> H256 {
+			merkle::content_id(&self.content_root, self.file_size, self.chunk_size)
+		}
+	}
+
+	/// Contents stored once for every file uploaded with the same content root, size and chunk
+	/// size, keyed by the content id they make up. Providers, challenges and replication deal with
+	/// contents rather than files.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ContentRecord {
+		pub content_root: H256,
+		pub file_size: u64,
+		pub chunk_size: u32,
+		/// Highest replication factor asked for by the files that referred to the content.
+		pub replication_factor: u32,
+		/// Number of files referring to the content.
+		pub refs: u32,
+	}
+
+	impl ContentRecord {
+		pub fn chunk_count(&self) -> u32 {
+			merkle::chunk_count(self.file_size, self.chunk_size)
+		}
+
+		/// Checks that `chunk` is chunk `index` of the content, given the sibling hashes in `proof`.
+		pub fn verify_chunk(&self, index: u32, chunk: &[u8], proof: &[H256]) -> bool {
+			chunk.len() <= self.chunk_size as usize &&
+				merkle::verify(&self.content_root, merkle::hash_leaf(chunk), index, self.chunk_count(), proof)
+		}
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum ProviderStatus<BlockNumber> {
+		/// The provider accepts new files.
+		Active,
+		/// The provider has left and its stake can be withdrawn from the given block.
+		Unbonding(BlockNumber),
+	}
+
+	/// An account that hosts file contents in exchange for a bonded stake.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct StorageProvider<T: Config> {
+		pub stake: BalanceOf<T>,
+		/// Total bytes the provider is willing to host.
+		pub capacity: u64,
+		/// Bytes taken up by the files currently assigned to the provider.
+		pub used: u64,
+		pub price_per_byte: u64,
+		pub status: ProviderStatus<T::BlockNumber>,
+	}
+
+	/// A provider picked to store one more copy of some contents, paid from their storage fund once
+	/// it takes them on.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct ReplicationOrder<T: Config> {
+		pub provider: T::AccountId,
+		pub reward: BalanceOf<T>,
+		pub expires_at: T::BlockNumber,
+	}
+
+	/// Extra storage an account can buy on top of `DefaultQuota`.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct QuotaPlan<Balance> {
+		pub bytes: u64,
+		pub price: Balance,
+	}
+
+	/// Calls an operator may make on behalf of an account.
+	#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct OperatorScope {
+		pub upload: bool,
+		/// Changing the cost and download permission of a file.
+		pub update: bool,
+		pub transfer: bool,
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct OperatorApproval<BlockNumber> {
+		pub scope: OperatorScope,
+		/// Last block in which the operator may act, if any.
+		pub expires_at: Option<BlockNumber>,
+	}
+
+	/// How long the rent of a file is paid for.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct RentStatus<BlockNumber> {
+		pub paid_through: BlockNumber,
+		/// Set once the owner has been warned that the rent is overdue.
+		pub expired: bool,
+	}
+
+	/// An open request for a provider to prove that it still holds a chunk of some contents.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Challenge<T: Config> {
+		pub content_id: H256,
+		pub chunk_index: u32,
+		/// Last block in which the provider can respond.
+		pub deadline: T::BlockNumber,
+	}
+
+	/// Right of a buyer to fetch the version of a file they bought again without paying.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct License {
+		pub version: u32,
+		pub tier: LicenseTier,
+		/// Terms of the file when it was bought.
+		pub kind: LicenseKind,
+		pub terms_hash: Option<H256>,
+		/// Fetches left, or `None` if the file can be fetched any number of times.
+		pub fetches: Option<u32>,
+	}
+
+	/// Payment for an encrypted file, held until the owner delivers the file key to the buyer.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct KeyRequest<T: Config> {
+		pub version: u32,
+		/// Reserved from the buyer until the key is delivered or the request is refunded.
+		pub amount: BalanceOf<T>,
+		/// Last block in which the key can be delivered.
+		pub deadline: T::BlockNumber,
+	}
+
+	/// A file put up for sale by its owner.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Listing<T: Config> {
+		/// Owner when the file was listed. The listing is dropped once the file changes hands.
+		pub seller: T::AccountId,
+		pub price: BalanceOf<T>,
+	}
+
+	/// Accounts managing a file together through proposals.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct CoOwnership<T: Config> {
+		pub owners: BoundedVec<T::AccountId, T::MaxCoOwners>,
+		/// Approvals a proposal needs to be carried out.
+		pub threshold: u32,
+		pub next_proposal_id: u32,
+		/// Proposals waiting for approvals, up to `MaxProposals`.
+		pub open_proposals: u32,
+	}
+
+	/// Change to a co-owned file that needs the approval of its co-owners.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum FileAction<AccountId, Balance, BlockNumber, Envelope, CoOwners> {
+		/// Sets the download cost and whether the file can be downloaded.
+		Update(u64, bool),
+		SetLicense(LicenseTerms),
+		Transfer(AccountId),
+		List(Balance),
+		Delist,
+		/// Publishes a new version with the given link, size, content root and chunk size. The
+		/// proposer pays its upload fee.
+		PublishVersion([u8; 20], u64, H256, u32),
+		Rollback(u32),
+		GrantAccess(AccountId, Envelope),
+		RevokeAccess(AccountId),
+		/// Sells the file to the given bidder for the amount of their offer.
+		AcceptOffer(AccountId),
+		/// Auctions the file with the given reserve price, ending in the given block.
+		CreateAuction(Balance, BlockNumber),
+		/// Replaces the co-owners and the threshold. Other open proposals are dropped.
+		SetCoOwners(CoOwners, u32),
+	}
+
+	pub type FileActionOf<T> = FileAction<
+		AccountOf<T>,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+		BoundedVec<u8, <T as Config>::MaxEnvelopeLength>,
+		BoundedVec<AccountOf<T>, <T as Config>::MaxCoOwners>,
+	>;
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Proposal<T: Config> {
+		pub action: FileActionOf<T>,
+		/// Co-owners who approved the proposal, starting with the one who made it.
+		pub approvals: BoundedVec<T::AccountId, T::MaxCoOwners>,
+	}
+
+	/// A transfer of a file waiting for the recipient to accept it.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct PendingTransfer<T: Config> {
+		/// Owner when the transfer was offered. The offer is dropped once the file changes hands.
+		pub from: T::AccountId,
+		pub to: T::AccountId,
+		/// Last block in which the transfer can be accepted.
+		pub deadline: T::BlockNumber,
+	}
+
+	/// An English auction for the ownership of a file.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct Auction<T: Config> {
+		/// Owner when the auction was created. The auction is cancelled once the file changes hands.
+		pub seller: T::AccountId,
+		/// Lowest bid the file is sold for.
+		pub reserve: BalanceOf<T>,
+		/// Block at the start of which the auction is settled. Bids are taken until then.
+		pub end: T::BlockNumber,
+		/// Highest bid, reserved from the bidder until they are outbid or the auction ends.
+		pub bid: Option<(T::AccountId, BalanceOf<T>)>,
+	}
+
+	/// Selects which files are returned when listing files through the runtime API.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum FileFilter<AccountId> {
+		All,
+		Owner(AccountId),
+		Type(FileType),
+		/// Files whose `cost` lies within the inclusive range.
+		PriceRange(u64, u64),
 	}
 
+	/// Storage version written at genesis, and by `migrations::v1` on chains started before it.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
     #[pallet::pallet]
     #[pallet::generate_store(trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
-    pub trait Config: pallet_balances::Config + frame_system::Config {
+    pub trait Config: pallet_balances::Config + frame_system::Config + SendTransactionTypes<Call<Self>> {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-		type Currency: Currency<Self::AccountId>;
+		type Currency: ReservableCurrency<Self::AccountId>;
 
 		#[pallet::constant]
 		type MaxFilesUploaded: Get<u32>;
@@ -58,6 +471,177 @@ pub mod pallet {
 		#[pallet::constant]
 		type FileSizeLimit: Get<u64>;
 
+		/// Upper bound on the number of files returned by a single listing query.
+		#[pallet::constant]
+		type MaxPageSize: Get<u32>;
+
+		/// Upper bound on the number of files a single listing query examines, matching or not.
+		#[pallet::constant]
+		type MaxPageScan: Get<u32>;
+
+		/// Smallest stake a storage provider can register with.
+		#[pallet::constant]
+		type MinProviderStake: Get<BalanceOf<Self>>;
+
+		/// Number of blocks a deregistered provider waits before its stake is released.
+		#[pallet::constant]
+		type UnbondingPeriod: Get<Self::BlockNumber>;
+
+		#[pallet::constant]
+		type MaxProvidersPerFile: Get<u32>;
+
+		#[pallet::constant]
+		type MaxFilesPerProvider: Get<u32>;
+
+		/// Source of randomness used to pick which providers and chunks are challenged.
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+		/// Storage challenges are issued every `ChallengePeriod` blocks.
+		#[pallet::constant]
+		type ChallengePeriod: Get<Self::BlockNumber>;
+
+		/// Number of blocks a provider has to answer a challenge.
+		#[pallet::constant]
+		type ChallengeWindow: Get<Self::BlockNumber>;
+
+		/// Share of a provider's stake slashed for each missed or invalid response.
+		#[pallet::constant]
+		type ChallengeSlash: Get<Perbill>;
+
+		#[pallet::constant]
+		type MaxChallengesPerBlock: Get<u32>;
+
+		#[pallet::constant]
+		type MaxChunkSize: Get<u32>;
+
+		#[pallet::constant]
+		type MaxProofLength: Get<u32>;
+
+		/// Offchain workers check file links every `AvailabilityCheckInterval` blocks.
+		#[pallet::constant]
+		type AvailabilityCheckInterval: Get<Self::BlockNumber>;
+
+		/// Number of files sampled by each availability check.
+		#[pallet::constant]
+		type MaxAvailabilityChecks: Get<u32>;
+
+		/// HTTP gateway serving file links, which are appended to it hex encoded.
+		type AvailabilityGateway: Get<&'static str>;
+
+		/// Priority of the unsigned transactions reporting unavailable files.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Identifies the account holding the storage funds of files.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Blocks a provider has to take on a file after being picked to replicate it.
+		#[pallet::constant]
+		type ReplicationPeriod: Get<Self::BlockNumber>;
+
+		/// Maximum number of providers looked at when picking providers to replicate a file.
+		#[pallet::constant]
+		type MaxReplicationCandidates: Get<u32>;
+
+		/// Rent charged per byte and block to keep a file stored. Files pay no rent and never
+		/// expire when it is zero.
+		#[pallet::constant]
+		type RentPerByte: Get<BalanceOf<Self>>;
+
+		/// Blocks a file is kept after its rent runs out before it is removed.
+		#[pallet::constant]
+		type RentGracePeriod: Get<Self::BlockNumber>;
+
+		/// Blocks of rent paid to the accountant on upload, during which the file can be
+		/// downloaded before its owner extends the storage.
+		#[pallet::constant]
+		type InitialRentPeriod: Get<Self::BlockNumber>;
+
+		/// Bytes every account can own without buying a plan.
+		#[pallet::constant]
+		type DefaultQuota: Get<u64>;
+
+		/// Fee per byte paid into the storage fund of new contents on upload.
+		#[pallet::constant]
+		type UploadFeePerByte: Get<BalanceOf<Self>>;
+
+		/// Share of the upload fee paid when the contents are already stored.
+		#[pallet::constant]
+		type DuplicateUploadFee: Get<Perbill>;
+
+		/// Number of previous versions kept for each file. Publishing past it drops the oldest one.
+		#[pallet::constant]
+		type MaxVersions: Get<u32>;
+
+		/// Maximum length of an encrypted file key.
+		#[pallet::constant]
+		type MaxEnvelopeLength: Get<u32>;
+
+		/// Maximum number of accounts holding the key of a file, whether granted by its owner or
+		/// delivered to a buyer.
+		#[pallet::constant]
+		type MaxKeyEnvelopes: Get<u32>;
+
+		/// Blocks an owner has to deliver the key of an encrypted file after it is bought. The
+		/// buyer is refunded afterwards.
+		#[pallet::constant]
+		type KeyRequestTimeout: Get<Self::BlockNumber>;
+
+		#[pallet::constant]
+		type MaxKeyRequestsPerBlock: Get<u32>;
+
+		/// NFT pallet holding one item per file, with the file id as item id.
+		type Nfts: nonfungibles::Inspect<Self::AccountId, ItemId = Self::Hash>
+			+ nonfungibles::Create<Self::AccountId>
+			+ nonfungibles::Mutate<Self::AccountId>
+			+ nonfungibles::Transfer<Self::AccountId>;
+
+		/// Collection the file NFTs are minted in. It is created at genesis, or by the v1 migration
+		/// on older chains, and owned by `nft_account`, which pays the NFT pallet's deposits. The
+		/// NFT pallet's `CreateOrigin` should be wrapped in `EnsureNotNftCollection` so nobody else
+		/// can claim it.
+		type NftCollection: Get<CollectionIdOf<Self>>;
+
+		/// Maximum number of open offers on a file. Once full, a new offer has to beat the lowest
+		/// one, which is refunded.
+		#[pallet::constant]
+		type MaxOffersPerFile: Get<u32>;
+
+		/// Maximum number of auctions ending in the same block.
+		#[pallet::constant]
+		type MaxAuctionsPerBlock: Get<u32>;
+
+		/// Maximum number of blocks an auction can run for, so bids are not reserved forever.
+		#[pallet::constant]
+		type MaxAuctionDuration: Get<Self::BlockNumber>;
+
+		/// Blocks a recipient has to accept a transfer offered to them.
+		#[pallet::constant]
+		type TransferOfferTimeout: Get<Self::BlockNumber>;
+
+		#[pallet::constant]
+		type MaxTransferOffersPerBlock: Get<u32>;
+
+		#[pallet::constant]
+		type MaxCoOwners: Get<u32>;
+
+		/// Maximum number of proposals waiting for approvals on a co-owned file.
+		#[pallet::constant]
+		type MaxProposals: Get<u32>;
+
+		/// Maximum number of files uploaded or downloaded in one batch.
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		/// Fetches a license grants on top of the download it was bought with. `None` grants
+		/// unlimited fetches.
+		#[pallet::constant]
+		type FetchesPerLicense: Get<Option<u32>>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+
 		// #[pallet::constant]
 		// type Accountant: Get<Self::AccountId>;
 
@@ -76,15 +660,179 @@ pub mod pallet {
 		InvalidSigner,
 		NotEnoughBalance,
 		AccountantNotSet,
-		FileDoesntExist
+		FileDoesntExist,
+		ProviderAlreadyRegistered,
+		ProviderNotFound,
+		InsufficientStake,
+		ProviderNotActive,
+		ProviderStillUnbonding,
+		ProviderNotUnbonding,
+		NotEnoughCapacity,
+		CapacityBelowUsage,
+		ProviderAlreadyAssigned,
+		/// The provider was already asked to store the contents.
+		ProviderAlreadyRequested,
+		ProviderNotAssigned,
+		ExceedMaxProvidersPerFile,
+		ExceedMaxFilesPerProvider,
+		InvalidChunkSize,
+		ChallengeNotFound,
+		ChallengePending,
+		InvalidReplicationFactor,
+		ReplicationOrderNotFound,
+		ReplicationOrderExpired,
+		RentNotRequired,
+		StorageExpired,
+		QuotaExceeded,
+		PlanNotFound,
+		FileAlreadyExists,
+		TooManyVersions,
+		TooManyEnvelopes,
+		VersionNotFound,
+		FileNotEncrypted,
+		EnvelopeNotFound,
+		KeyRequestPending,
+		KeyRequestNotFound,
+		TooManyKeyRequests,
+		InvalidKeyDelivery,
+		InvalidReport,
+		NotListed,
+		CannotBuyOwnFile,
+		PriceAboveLimit,
+		OfferNotFound,
+		OfferTooLow,
+		AuctionInProgress,
+		AuctionNotFound,
+		AuctionEnded,
+		/// The auction has to end in a later block.
+		InvalidAuctionEnd,
+		/// The auction would run for longer than `MaxAuctionDuration`.
+		AuctionTooLong,
+		TooManyAuctions,
+		/// A bid has to reach the reserve price and beat the highest bid.
+		BidTooLow,
+		AuctionHasBids,
+		TransferNotFound,
+		TooManyTransferOffers,
+		/// The threshold has to be at least one and at most the number of co-owners.
+		InvalidThreshold,
+		DuplicateCoOwner,
+		NotCoOwner,
+		ProposalNotFound,
+		TooManyProposals,
+		AlreadyApproved,
+		/// The caller is not an operator of the account for this call.
+		NotOperator,
+		OperatorExpired,
+		LicenseNotFound,
+		NoFetchesLeft,
+		InvalidLicense,
+		CommercialUseNotOffered,
     }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
 		Uploaded(T::AccountId, T::Hash),
-		Downloaded(T::AccountId, T::Hash, BalanceOf<T>),
+		/// An account bought the given version of a file.
+		Downloaded(T::AccountId, T::Hash, u32, BalanceOf<T>),
 		Transfered(T::AccountId, T::AccountId, T::Hash),
+		/// A storage provider registered with the given stake.
+		ProviderRegistered(T::AccountId, BalanceOf<T>),
+		/// A storage provider advertised a new capacity and price per byte.
+		ProviderUpdated(T::AccountId, u64, u64),
+		/// A storage provider left and can withdraw its stake from the given block.
+		ProviderUnbonding(T::AccountId, T::BlockNumber),
+		/// A storage provider withdrew its stake and was removed.
+		ProviderDeregistered(T::AccountId, BalanceOf<T>),
+		ContentAssigned(H256, T::AccountId),
+		ContentUnassigned(H256, T::AccountId),
+		/// A provider must prove it holds the given chunk of the contents before the deadline.
+		ChallengeIssued(T::AccountId, H256, u32, T::BlockNumber),
+		ChallengePassed(T::AccountId, H256),
+		/// A provider answered a challenge with an invalid proof.
+		ChallengeFailed(T::AccountId, H256),
+		/// A provider did not answer a challenge in time.
+		ChallengeMissed(T::AccountId, H256),
+		ProviderSlashed(T::AccountId, BalanceOf<T>),
+		/// An offchain worker could not fetch the file link from the gateway.
+		FileUnavailable(T::Hash),
+		FileFunded(T::Hash, T::AccountId, BalanceOf<T>),
+		/// A provider was picked to store a copy of the contents before the given block.
+		ReplicationOrderOpened(H256, T::AccountId, BalanceOf<T>, T::BlockNumber),
+		ReplicationOrderExpired(H256, T::AccountId),
+		ReplicationFulfilled(H256, T::AccountId, BalanceOf<T>),
+		/// The rent of a file is now paid through the given block.
+		StorageExtended(T::Hash, T::BlockNumber),
+		/// The rent of a file ran out. The file is removed after the given block unless its owner
+		/// extends the storage.
+		FileExpired(T::Hash, T::BlockNumber),
+		FileRemoved(T::Hash),
+		/// A file was uploaded with contents already stored, now referred to by the given number of
+		/// files.
+		ContentShared(T::Hash, H256, u32),
+		/// The last file referring to the contents was removed.
+		ContentRemoved(H256),
+		VersionPublished(T::Hash, u32),
+		/// The oldest version of a file was dropped to make room in its history, along with its
+		/// downloads and licenses.
+		VersionDropped(T::Hash, u32),
+		/// A file went back to serving one of its previous versions.
+		RolledBack(T::Hash, u32),
+		/// The owner stored the file key for the given recipient.
+		AccessGranted(T::Hash, T::AccountId),
+		AccessRevoked(T::Hash, T::AccountId),
+		DeliveryKeySet(T::AccountId),
+		/// The key was allowed, or no longer allowed, to sign availability reports.
+		ReporterSet(sr25519::Public, bool),
+		/// A buyer of an encrypted file waits for its key until the given block.
+		KeyRequested(T::Hash, T::AccountId, T::BlockNumber),
+		/// The key was delivered and the held payment went to the accountant.
+		KeyRequestFulfilled(T::Hash, T::AccountId),
+		KeyRequestRefunded(T::Hash, T::AccountId, BalanceOf<T>),
+		/// A file was put up for sale by the given owner at the given price.
+		Listed(T::Hash, T::AccountId, BalanceOf<T>),
+		Delisted(T::Hash),
+		OfferMade(T::Hash, T::AccountId, BalanceOf<T>),
+		/// An offer was withdrawn or outbid, and the amount went back to the bidder.
+		OfferWithdrawn(T::Hash, T::AccountId),
+		/// A file was sold by the first account to the second.
+		Sold(T::Hash, T::AccountId, T::AccountId, BalanceOf<T>),
+		/// An auction was opened by the given owner with a reserve price, ending at the given block.
+		AuctionCreated(T::Hash, T::AccountId, BalanceOf<T>, T::BlockNumber),
+		BidPlaced(T::Hash, T::AccountId, BalanceOf<T>),
+		/// A bidder was outbid and got the bid back.
+		Outbid(T::Hash, T::AccountId, BalanceOf<T>),
+		/// An auction ended without a sale, and any bid went back to the bidder.
+		AuctionCancelled(T::Hash),
+		/// The owner offered a file to the second account, which can accept it until the given block.
+		TransferOffered(T::Hash, T::AccountId, T::AccountId, T::BlockNumber),
+		/// A transfer offer was turned down by the recipient or taken back by the owner.
+		TransferRejected(T::Hash, T::AccountId),
+		TransferOfferExpired(T::Hash),
+		/// A file now has the given cost and download permission.
+		FileUpdated(T::Hash, u64, bool),
+		LicenseSet(T::Hash, LicenseTerms),
+		/// A file moved to the given account shared by its co-owners, with the number of approvals
+		/// their proposals need.
+		CoOwnersSet(T::Hash, T::AccountId, u32),
+		Proposed(T::Hash, u32, T::AccountId),
+		Approved(T::Hash, u32, T::AccountId),
+		ProposalExecuted(T::Hash, u32),
+		ProposalCancelled(T::Hash, u32),
+		/// An account let the second account make the given calls for it until the given block.
+		OperatorApproved(T::AccountId, T::AccountId, OperatorScope, Option<T::BlockNumber>),
+		OperatorRevoked(T::AccountId, T::AccountId),
+		/// An operator made a call on a file for the second account.
+		OperatorActed(T::AccountId, T::AccountId, T::Hash),
+		/// A license holder fetched the given version of a file again, with the fetches left.
+		Fetched(T::Hash, T::AccountId, u32, Option<u32>),
+		/// A buyer upgraded their personal license of the given version to commercial use, paying
+		/// the difference.
+		LicenseUpgraded(T::Hash, T::AccountId, u32, BalanceOf<T>),
+		PlanUpdated(u32),
+		/// An account bought a plan, raising its quota to the given number of bytes.
+		PlanBought(T::AccountId, u32, u64),
     }
 
 	#[pallet::storage]
@@ -111,161 +859,2907 @@ pub mod pallet {
 	#[pallet::storage]
 	#[pallet::getter(fn get_download_details)]
 	// I am trying to map hash to a vector with account id and file. So i can tell which user downloaded which file
-	pub(super) type FileDownloads<T: Config> = StorageMap<_, Twox64Concat, T::Hash, BoundedVec<T::AccountId,T::MaxFilesUploaded>, ValueQuery>;
+	// Downloads are kept per version, and buying one version gives no right to the others.
+	pub(super) type FileDownloads<T: Config> = StorageDoubleMap<_, Twox64Concat, T::Hash, Twox64Concat, u32, BoundedVec<T::AccountId,T::MaxFilesUploaded>, ValueQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn licenses)]
+	// License each buyer holds for every version of a file they bought.
+	pub(super) type Licenses<T: Config> = StorageDoubleMap<_, Twox64Concat, T::Hash, Twox64Concat, (T::AccountId, u32), License>;
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-		// Upload
-		
-		#[pallet::weight(100)]
-		pub fn add_accountant(origin: OriginFor<T>) -> DispatchResult {
+	#[pallet::storage]
+	#[pallet::getter(fn key_envelope)]
+	// Key of an encrypted file, encrypted by its owner under the public key of each recipient.
+	pub(super) type KeyEnvelopes<T: Config> = StorageDoubleMap<_, Twox64Concat, T::Hash, Twox64Concat, T::AccountId, BoundedVec<u8, T::MaxEnvelopeLength>>;
 
-			let signer = ensure_signed(origin)?;
+	#[pallet::storage]
+	// Number of key envelopes of each file, up to `MaxKeyEnvelopes`.
+	pub(super) type KeyEnvelopeCount<T: Config> = StorageMap<_, Twox64Concat, T::Hash, u32, ValueQuery>;
 
-			let account = Self::accountant();
+	#[pallet::storage]
+	#[pallet::getter(fn key_requests)]
+	// Purchases of encrypted files waiting for the owner to deliver the file key.
+	pub(super) type KeyRequests<T: Config> = StorageDoubleMap<_, Twox64Concat, T::Hash, Twox64Concat, T::AccountId, KeyRequest<T>>;
 
-			match account {
-				Some(acc) => {
-					ensure!(acc == signer, <Error<T>>::InvalidSigner);
-				},
-				None => {
-					<Accountant<T>>::put(signer);
-				}
-			}
+	#[pallet::storage]
+	// Key requests whose deadline passes as the given block starts.
+	pub(super) type KeyRequestDeadlines<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<(T::Hash, T::AccountId), T::MaxKeyRequestsPerBlock>, ValueQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn delivery_key)]
+	// Key signing the deliveries made by the offchain worker of each owner.
+	pub(super) type DeliveryKeys<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, sr25519::Public>;
 
-			Ok(())
-		}
+	#[pallet::storage]
+	// Keys whose offchain workers may sign availability reports.
+	pub(super) type AvailabilityReporters<T: Config> = StorageMap<_, Twox64Concat, sr25519::Public, ()>;
 
-		#[pallet::weight(100)]
-		pub fn upload_file(origin: OriginFor<T>, file_link: [u8; 20], allow_download: bool, file_type: FileType, cost: u64, file_size: u64) -> DispatchResult {
-			let sender = ensure_signed(origin)?;
+	#[pallet::storage]
+	#[pallet::getter(fn listings)]
+	pub(super) type Listings<T: Config> = StorageMap<_, Twox64Concat, T::Hash, Listing<T>>;
 
-			let file = File::<T> {
-				file_link,
-				allow_download,
-				file_type,
-				cost,
-				file_size,
-				owner: sender.clone()
-			};
+	#[pallet::storage]
+	#[pallet::getter(fn offers)]
+	// Offers on each file with the amounts reserved from the bidders. A bidder has at most one
+	// offer per file.
+	pub(super) type Offers<T: Config> = StorageMap<_, Twox64Concat, T::Hash, BoundedVec<(T::AccountId, BalanceOf<T>), T::MaxOffersPerFile>, ValueQuery>;
 
-			let file_id = T::Hashing::hash_of(&file);
-			let new_count = Self::all_files_count().checked_add(1).ok_or(<Error<T>>::FileCountOverflow)?;
+	#[pallet::storage]
+	#[pallet::getter(fn operators)]
+	// Operators each account lets act on its behalf.
+	pub(super) type Operators<T: Config> = StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, OperatorApproval<T::BlockNumber>>;
 
-			<FilesPerUser<T>>::try_mutate(&sender, |file_vec| file_vec.try_push(file_id))
-			.map_err(|_| <Error<T>>::ExceedMaxFileUploaded)?;
+	#[pallet::storage]
+	#[pallet::getter(fn co_owners)]
+	// Co-owners of the files owned by their shared account.
+	pub(super) type CoOwners<T: Config> = StorageMap<_, Twox64Concat, T::Hash, CoOwnership<T>>;
 
-			<Files<T>>::insert(file_id, file);
-			<AllFilesCount<T>>::put(new_count);
+	#[pallet::storage]
+	#[pallet::getter(fn proposals)]
+	// Changes to co-owned files waiting for enough approvals.
+	pub(super) type Proposals<T: Config> = StorageDoubleMap<_, Twox64Concat, T::Hash, Twox64Concat, u32, Proposal<T>>;
 
-			Self::deposit_event(Event::Uploaded(sender, file_id));
+	#[pallet::storage]
+	#[pallet::getter(fn pending_transfers)]
+	pub(super) type PendingTransfers<T: Config> = StorageMap<_, Twox64Concat, T::Hash, PendingTransfer<T>>;
 
-			Ok(())
-		}
+	#[pallet::storage]
+	// Transfer offers whose deadline passes as the given block starts.
+	pub(super) type TransferOfferDeadlines<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<T::Hash, T::MaxTransferOffersPerBlock>, ValueQuery>;
 
-		// Download 
-		#[pallet::weight(100)]
-		pub fn download_file(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+	#[pallet::storage]
+	#[pallet::getter(fn auctions)]
+	pub(super) type Auctions<T: Config> = StorageMap<_, Twox64Concat, T::Hash, Auction<T>>;
 
-			let signer = ensure_signed(origin)?;
+	#[pallet::storage]
+	// Auctions settled as the given block starts.
+	pub(super) type AuctionEnds<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<T::Hash, T::MaxAuctionsPerBlock>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn file_versions)]
+	// Previous versions of each file. The current version lives in `Files`.
+	pub(super) type FileVersions<T: Config> = StorageMap<_, Twox64Concat, T::Hash, BoundedVec<FileVersion, T::MaxVersions>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn providers)]
+	pub(super) type Providers<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, StorageProvider<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn contents)]
+	// Keyed by content id.
+	pub(super) type Contents<T: Config> = StorageMap<_, Twox64Concat, H256, ContentRecord>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn content_providers)]
+	pub(super) type ContentProviders<T: Config> = StorageMap<_, Twox64Concat, H256, BoundedVec<T::AccountId, T::MaxProvidersPerFile>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn provider_contents)]
+	pub(super) type ProviderContents<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, BoundedVec<H256, T::MaxFilesPerProvider>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn challenges)]
+	// A provider has at most one open challenge at a time.
+	pub(super) type Challenges<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Challenge<T>>;
+
+	#[pallet::storage]
+	// Providers whose challenge deadline passes as the given block starts.
+	pub(super) type ChallengeDeadlines<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<T::AccountId, T::MaxChallengesPerBlock>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn storage_funds)]
+	// Balance set aside in the pallet account to pay providers for replicating the contents.
+	pub(super) type StorageFunds<T: Config> = StorageMap<_, Twox64Concat, H256, BalanceOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn replication_orders)]
+	pub(super) type ReplicationOrders<T: Config> = StorageMap<_, Twox64Concat, H256, BoundedVec<ReplicationOrder<T>, T::MaxProvidersPerFile>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn bytes_used)]
+	// Sum of the sizes of the files each account owns.
+	pub(super) type BytesUsed<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn extra_quota)]
+	// Bytes bought through plans on top of `DefaultQuota`.
+	pub(super) type ExtraQuota<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn quota_plans)]
+	pub(super) type QuotaPlans<T: Config> = StorageMap<_, Twox64Concat, u32, QuotaPlan<BalanceOf<T>>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn file_rent)]
+	// Only files uploaded while rent is charged have an entry.
+	pub(super) type FileRent<T: Config> = StorageMap<_, Twox64Concat, T::Hash, RentStatus<T::BlockNumber>>;
+
+	#[pallet::storage]
+	// Last file checked for expiry, where the next `on_idle` picks up.
+	pub(super) type RentCursor<T: Config> = StorageValue<_, T::Hash>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_availability_report)]
+	// Reports of availability checks made before this block are stale.
+	pub(super) type NextAvailabilityReport<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			// The collection exists before the first upload, so nobody can take its id first.
+			Pallet::<T>::create_nft_collection().expect("the NFT account can pay the collection deposit");
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let refunded = Self::expire_key_requests(now);
+			let settled = Self::settle_auctions(now);
+			let withdrawn = Self::expire_transfer_offers(now);
+			let expired = Self::expire_challenges(now);
+
+			let period = T::ChallengePeriod::get();
+			let issued = if !period.is_zero() && (now % period).is_zero() {
+				Self::issue_challenges(now)
+			} else {
+				0
+			};
+
+			// Every expired challenge may trigger a re-replication of the challenged contents.
+			T::WeightInfo::on_initialize(expired, issued)
+				.saturating_add(T::WeightInfo::replicate().saturating_mul(expired as Weight))
+				.saturating_add(T::WeightInfo::expire_key_requests(refunded))
+				.saturating_add(T::WeightInfo::settle_auctions(settled))
+				.saturating_add(T::WeightInfo::expire_transfer_offers(withdrawn))
+		}
+
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::collect_expired(now, remaining_weight)
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			let interval = T::AvailabilityCheckInterval::get();
+			if !interval.is_zero() && (now % interval).is_zero() {
+				if let Err(e) = Self::check_file_availability(now) {
+					log::warn!(target: "runtime::mydropbox", "Availability check failed: {}", e);
+				}
+			}
+
+			Self::deliver_keys(now);
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(Self::on_chain_storage_version() == STORAGE_VERSION, "storage was not migrated");
+			Self::do_try_state()
+		}
+	}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+		// Upload
+		
+		#[pallet::weight(T::WeightInfo::add_accountant())]
+		pub fn add_accountant(origin: OriginFor<T>) -> DispatchResult {
+
+			let signer = ensure_signed(origin)?;
+
+			let account = Self::accountant();
+
+			match account {
+				Some(acc) => {
+					ensure!(acc == signer, <Error<T>>::InvalidSigner);
+				},
+				None => {
+					<Accountant<T>>::put(signer);
+				}
+			}
+
+
+			Ok(())
+		}
+
+		// Files with the same content root share a single copy of the contents, and only the first
+		// upload pays the full fee into its storage fund.
+		#[pallet::weight(T::WeightInfo::upload_file(T::MaxFilesUploaded::get()))]
+		#[allow(clippy::too_many_arguments)]
+		#[transactional]
+		pub fn upload_file(
+			origin: OriginFor<T>,
+			file_link: [u8; 20],
+			allow_download: bool,
+			file_type: FileType,
+			cost: u64,
+			file_size: u64,
+			content_root: H256,
+			chunk_size: u32,
+			replication_factor: u32,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			Self::do_upload(File::<T> {
+				file_link,
+				allow_download,
+				file_type,
+				cost,
+				file_size,
+				owner: sender,
+				content_root,
+				chunk_size,
+				replication_factor,
+				version: 0,
+				license: LicenseTerms::default(),
+			})?;
+			Ok(())
+		}
+
+		// Download 
+		// Buys the given version of the file, or the current one when no version is given.
+		#[pallet::weight(Pallet::<T>::download_file_weight(T::MaxFilesUploaded::get()))]
+		#[transactional]
+		pub fn download_file(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			version: Option<u32>,
+			tier: LicenseTier,
+		) -> DispatchResultWithPostInfo {
+
+			let signer = ensure_signed(origin)?;
+
+			let actual_weight = Self::do_download(&signer, &file_id, version, tier)?;
+			Ok(Some(actual_weight).into())
+		}
+
+		// Fetches a version of the file again under the caller's license for it, using up one of its
+		// fetches. The current version is fetched when no version is given.
+		#[pallet::weight(T::WeightInfo::fetch_file())]
+		pub fn fetch_file(origin: OriginFor<T>, file_id: T::Hash, version: Option<u32>) -> DispatchResult {
+			let holder = ensure_signed(origin)?;
+
+			let file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			ensure!(!Self::rent_overdue(&file_id), <Error<T>>::StorageExpired);
+			let key = (holder.clone(), version.unwrap_or(file.version));
+			let mut license = Self::licenses(&file_id, &key).ok_or(<Error<T>>::LicenseNotFound)?;
+			// Encrypted files are only paid for once the key is delivered.
+			ensure!(!<KeyRequests<T>>::contains_key(&file_id, &holder), <Error<T>>::KeyRequestPending);
+
+			if let Some(fetches) = license.fetches {
+				license.fetches = Some(fetches.checked_sub(1).ok_or(<Error<T>>::NoFetchesLeft)?);
+				<Licenses<T>>::insert(&file_id, &key, license);
+			}
+
+			Self::deposit_event(Event::Fetched(file_id, holder, license.version, license.fetches));
+			Ok(())
+		}
+
+		// Uploads several files at once. Either every file is uploaded or none is.
+		#[pallet::weight(T::WeightInfo::upload_file(T::MaxFilesUploaded::get()).saturating_mul(files.len() as Weight))]
+		#[transactional]
+		pub fn upload_files(origin: OriginFor<T>, files: BoundedVec<FileUpload, T::MaxBatchSize>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			for upload in files {
+				Self::do_upload(File::<T>::new(sender.clone(), upload))?;
+			}
+			Ok(())
+		}
+
+		// Buys several files at once, each at the given version or the current one and for the
+		// given use. Either every file is bought or none is.
+		#[pallet::weight(Pallet::<T>::download_file_weight(T::MaxFilesUploaded::get()).saturating_mul(files.len() as Weight))]
+		#[transactional]
+		pub fn download_files(
+			origin: OriginFor<T>,
+			files: BoundedVec<(T::Hash, Option<u32>, LicenseTier), T::MaxBatchSize>,
+		) -> DispatchResultWithPostInfo {
+			let signer = ensure_signed(origin)?;
+
+			let mut actual_weight: Weight = 0;
+			for (file_id, version, tier) in files {
+				let weight = Self::do_download(&signer, &file_id, version, tier)?;
+				actual_weight = actual_weight.saturating_add(weight);
+			}
+			Ok(Some(actual_weight).into())
+		}
+
+		// Transfer
+		// Offers the file to `new_owner`, who has to accept it before the offer times out, so nobody
+		// is handed a file without consent. Replaces any previous offer.
+		#[pallet::weight(T::WeightInfo::transfer_file())]
+		pub fn transfer_file(origin: OriginFor<T>, file_id: T::Hash, new_owner: T::AccountId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			
+			Self::do_transfer(&owner, &file_id, new_owner)
+		}
+
+		// Takes over a file offered to the caller, along with its NFT.
+		#[pallet::weight(T::WeightInfo::accept_transfer(T::MaxFilesUploaded::get()))]
+		#[transactional]
+		pub fn accept_transfer(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			let new_owner = ensure_signed(origin)?;
+
+			let pending = Self::pending_transfers(&file_id).ok_or(<Error<T>>::TransferNotFound)?;
+			ensure!(pending.to == new_owner, <Error<T>>::TransferNotFound);
+			let mut file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			ensure!(file.owner == pending.from, <Error<T>>::TransferNotFound);
+			ensure!(!<Auctions<T>>::contains_key(&file_id), <Error<T>>::AuctionInProgress);
+
+			// Moving the file drops the offer.
+			Self::move_file(&file_id, &mut file, &new_owner)?;
+
+			Self::deposit_event(Event::Transfered(pending.from, new_owner, file_id));
+			Ok(())
+		}
+
+		// Turns down a transfer offered to the caller, or takes back one the caller offered.
+		#[pallet::weight(T::WeightInfo::reject_transfer())]
+		pub fn reject_transfer(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let pending = Self::pending_transfers(&file_id).ok_or(<Error<T>>::TransferNotFound)?;
+			ensure!(pending.to == who || pending.from == who, <Error<T>>::TransferNotFound);
+			Self::remove_transfer_offer(&file_id);
+
+			Self::deposit_event(Event::TransferRejected(file_id, who));
+			Ok(())
+		}
+
+		// Sets the download cost of the file and whether it can be downloaded.
+		#[pallet::weight(T::WeightInfo::update_file())]
+		pub fn update_file(origin: OriginFor<T>, file_id: T::Hash, cost: u64, allow_download: bool) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_update_file(&file_id, file, cost, allow_download);
+			Ok(())
+		}
+
+		// Sets the license buyers of the file agree to, and what commercial use of it costs.
+		// Licenses already bought keep the terms they were bought under.
+		#[pallet::weight(T::WeightInfo::set_license())]
+		pub fn set_license(origin: OriginFor<T>, file_id: T::Hash, license: LicenseTerms) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_set_license(&file_id, file, license)
+		}
+
+		// Hands the file over to an account shared by `owners`. Any change to the file then needs
+		// a proposal approved by `threshold` of them, and its sales are split among them.
+		#[pallet::weight(T::WeightInfo::set_co_owners(T::MaxFilesUploaded::get()))]
+		#[transactional]
+		pub fn set_co_owners(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			owners: BoundedVec<T::AccountId, T::MaxCoOwners>,
+			threshold: u32,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_set_co_owners(&file_id, file, owners, threshold)
+		}
+
+		// Proposes a change to a co-owned file, approved by the caller. It is carried out as soon
+		// as enough co-owners approve it.
+		#[pallet::weight(T::WeightInfo::propose().saturating_add(Pallet::<T>::file_action_weight()))]
+		#[transactional]
+		pub fn propose(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			action: FileActionOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut co_ownership = Self::co_owners(&file_id).ok_or(<Error<T>>::NotCoOwner)?;
+			ensure!(co_ownership.owners.contains(&who), <Error<T>>::NotCoOwner);
+			ensure!(co_ownership.open_proposals < T::MaxProposals::get(), <Error<T>>::TooManyProposals);
+			let proposal_id = co_ownership.next_proposal_id;
+			co_ownership.next_proposal_id = proposal_id.checked_add(1).ok_or(<Error<T>>::InvalidOperation)?;
+			co_ownership.open_proposals += 1;
+			<CoOwners<T>>::insert(&file_id, &co_ownership);
+
+			let mut approvals = BoundedVec::default();
+			approvals.try_push(who.clone()).map_err(|_| <Error<T>>::NotCoOwner)?;
+			Self::deposit_event(Event::Proposed(file_id, proposal_id, who));
+			Self::approve_proposal(&file_id, proposal_id, co_ownership.threshold, Proposal::<T> { action, approvals })
+		}
+
+		#[pallet::weight(T::WeightInfo::approve().saturating_add(Pallet::<T>::file_action_weight()))]
+		#[transactional]
+		pub fn approve(origin: OriginFor<T>, file_id: T::Hash, proposal_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let co_ownership = Self::co_owners(&file_id).ok_or(<Error<T>>::NotCoOwner)?;
+			ensure!(co_ownership.owners.contains(&who), <Error<T>>::NotCoOwner);
+			let mut proposal = Self::proposals(&file_id, proposal_id).ok_or(<Error<T>>::ProposalNotFound)?;
+			ensure!(!proposal.approvals.contains(&who), <Error<T>>::AlreadyApproved);
+			proposal.approvals.try_push(who.clone()).map_err(|_| <Error<T>>::AlreadyApproved)?;
+
+			Self::deposit_event(Event::Approved(file_id, proposal_id, who));
+			Self::approve_proposal(&file_id, proposal_id, co_ownership.threshold, proposal)
+		}
+
+		// Drops a proposal made by the caller.
+		#[pallet::weight(T::WeightInfo::cancel_proposal())]
+		pub fn cancel_proposal(origin: OriginFor<T>, file_id: T::Hash, proposal_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let proposal = Self::proposals(&file_id, proposal_id).ok_or(<Error<T>>::ProposalNotFound)?;
+			ensure!(proposal.approvals.first() == Some(&who), <Error<T>>::InvalidSigner);
+			Self::close_proposal(&file_id, proposal_id);
+
+			Self::deposit_event(Event::ProposalCancelled(file_id, proposal_id));
+			Ok(())
+		}
+
+		// Lets `operator` make the calls in `scope` on behalf of the caller, through the last block
+		// `expires_at` if given. Replaces any previous approval of the operator.
+		#[pallet::weight(T::WeightInfo::approve_operator())]
+		pub fn approve_operator(
+			origin: OriginFor<T>,
+			operator: T::AccountId,
+			scope: OperatorScope,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(operator != who, <Error<T>>::InvalidOperation);
+			if let Some(expires_at) = expires_at {
+				ensure!(expires_at >= <frame_system::Pallet<T>>::block_number(), <Error<T>>::OperatorExpired);
+			}
+			<Operators<T>>::insert(&who, &operator, OperatorApproval { scope, expires_at });
+
+			Self::deposit_event(Event::OperatorApproved(who, operator, scope, expires_at));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_operator())]
+		pub fn revoke_operator(origin: OriginFor<T>, operator: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			<Operators<T>>::take(&who, &operator).ok_or(<Error<T>>::NotOperator)?;
+
+			Self::deposit_event(Event::OperatorRevoked(who, operator));
+			Ok(())
+		}
+
+		// Uploads a file owned by `owner`, made by one of its operators.
+		#[pallet::weight(T::WeightInfo::upload_file_for(T::MaxFilesUploaded::get()))]
+		#[allow(clippy::too_many_arguments)]
+		#[transactional]
+		pub fn upload_file_for(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			file_link: [u8; 20],
+			allow_download: bool,
+			file_type: FileType,
+			cost: u64,
+			file_size: u64,
+			content_root: H256,
+			chunk_size: u32,
+			replication_factor: u32,
+		) -> DispatchResult {
+			let operator = ensure_signed(origin)?;
+
+			Self::ensure_operator(&owner, &operator, |scope| scope.upload)?;
+			let file_id = Self::do_upload(File::<T> {
+				file_link,
+				allow_download,
+				file_type,
+				cost,
+				file_size,
+				owner: owner.clone(),
+				content_root,
+				chunk_size,
+				replication_factor,
+				version: 0,
+				license: LicenseTerms::default(),
+			})?;
+
+			Self::deposit_event(Event::OperatorActed(operator, owner, file_id));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::update_file_for())]
+		pub fn update_file_for(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			file_id: T::Hash,
+			cost: u64,
+			allow_download: bool,
+		) -> DispatchResult {
+			let operator = ensure_signed(origin)?;
+
+			Self::ensure_operator(&owner, &operator, |scope| scope.update)?;
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_update_file(&file_id, file, cost, allow_download);
+
+			Self::deposit_event(Event::OperatorActed(operator, owner, file_id));
+			Ok(())
+		}
+
+		// Offers the file on behalf of its owner. The recipient still has to accept it.
+		#[pallet::weight(T::WeightInfo::transfer_file_for())]
+		pub fn transfer_file_for(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			file_id: T::Hash,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			let operator = ensure_signed(origin)?;
+
+			Self::ensure_operator(&owner, &operator, |scope| scope.transfer)?;
+			Self::do_transfer(&owner, &file_id, new_owner)?;
+
+			Self::deposit_event(Event::OperatorActed(operator, owner, file_id));
+			Ok(())
+		}
+
+		// Puts the file up for sale, replacing the price of an existing listing.
+		#[pallet::weight(T::WeightInfo::list_file())]
+		pub fn list_file(origin: OriginFor<T>, file_id: T::Hash, price: BalanceOf<T>) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			Self::owned_file(&file_id, &owner)?;
+			ensure!(!<Auctions<T>>::contains_key(&file_id), <Error<T>>::AuctionInProgress);
+			<Listings<T>>::insert(&file_id, Listing::<T> { seller: owner.clone(), price });
+
+			Self::deposit_event(Event::Listed(file_id, owner, price));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::delist_file())]
+		pub fn delist_file(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			Self::owned_file(&file_id, &owner)?;
+			<Listings<T>>::take(&file_id).ok_or(<Error<T>>::NotListed)?;
+
+			Self::deposit_event(Event::Delisted(file_id));
+			Ok(())
+		}
+
+		// Pays the listed price to the owner and takes over the file. Fails if the price went above
+		// `max_price`.
+		#[pallet::weight(T::WeightInfo::buy_file(T::MaxFilesUploaded::get()))]
+		#[transactional]
+		pub fn buy_file(origin: OriginFor<T>, file_id: T::Hash, max_price: BalanceOf<T>) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+
+			let mut file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			let listing = Self::listings(&file_id).ok_or(<Error<T>>::NotListed)?;
+			let seller = file.owner.clone();
+			ensure!(buyer != seller, <Error<T>>::CannotBuyOwnFile);
+			ensure!(listing.price <= max_price, <Error<T>>::PriceAboveLimit);
+
+			Self::pay_owners(&file_id, &seller, listing.price, |to, share| {
+				T::Currency::transfer(&buyer, to, share, ExistenceRequirement::KeepAlive)
+			})?;
+			Self::sell_file(&file_id, &mut file, &buyer, listing.price)
+		}
+
+		// Reserves `amount` as an offer on the file, replacing the caller's previous offer.
+		#[pallet::weight(T::WeightInfo::make_offer())]
+		#[transactional]
+		pub fn make_offer(origin: OriginFor<T>, file_id: T::Hash, amount: BalanceOf<T>) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			ensure!(<Files<T>>::contains_key(&file_id), <Error<T>>::FileNotFound);
+			ensure!(!amount.is_zero(), <Error<T>>::OfferTooLow);
+
+			let mut offers = Self::offers(&file_id);
+			if let Some(index) = offers.iter().position(|(who, _)| *who == bidder) {
+				let (_, previous) = offers.remove(index);
+				T::Currency::unreserve(&bidder, previous);
+			}
+			if offers.len() as u32 >= T::MaxOffersPerFile::get() {
+				// The lowest offer makes room for a higher one.
+				let (index, lowest) = offers
+					.iter()
+					.enumerate()
+					.min_by_key(|(_, (_, amount))| *amount)
+					.map(|(index, (_, amount))| (index, *amount))
+					.ok_or(<Error<T>>::OfferTooLow)?;
+				ensure!(amount > lowest, <Error<T>>::OfferTooLow);
+				let (outbid, outbid_amount) = offers.remove(index);
+				T::Currency::unreserve(&outbid, outbid_amount);
+				Self::deposit_event(Event::OfferWithdrawn(file_id, outbid));
+			}
+
+			T::Currency::reserve(&bidder, amount)?;
+			offers.try_push((bidder.clone(), amount)).map_err(|_| <Error<T>>::OfferTooLow)?;
+			<Offers<T>>::insert(&file_id, offers);
+
+			Self::deposit_event(Event::OfferMade(file_id, bidder, amount));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::withdraw_offer())]
+		pub fn withdraw_offer(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			let mut offers = Self::offers(&file_id);
+			let index = offers.iter().position(|(who, _)| *who == bidder).ok_or(<Error<T>>::OfferNotFound)?;
+			let (_, amount) = offers.remove(index);
+			T::Currency::unreserve(&bidder, amount);
+			<Offers<T>>::insert(&file_id, offers);
+
+			Self::deposit_event(Event::OfferWithdrawn(file_id, bidder));
+			Ok(())
+		}
+
+		// Sells the file to `bidder` for the amount of their offer.
+		#[pallet::weight(T::WeightInfo::accept_offer(T::MaxFilesUploaded::get()))]
+		#[transactional]
+		pub fn accept_offer(origin: OriginFor<T>, file_id: T::Hash, bidder: T::AccountId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_accept_offer(&file_id, file, bidder)
+		}
+
+		// Auctions the file to the highest bid of at least `reserve`, settled as block `end`
+		// starts. The file is taken off sale until then.
+		#[pallet::weight(T::WeightInfo::create_auction())]
+		pub fn create_auction(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			reserve: BalanceOf<T>,
+			end: T::BlockNumber,
+		) -> DispatchResult {
+			let seller = ensure_signed(origin)?;
+
+			Self::owned_file(&file_id, &seller)?;
+			Self::do_create_auction(&file_id, seller, reserve, end)
+		}
+
+		// Reserves `amount` as the highest bid, refunding the previous one.
+		#[pallet::weight(T::WeightInfo::bid())]
+		#[transactional]
+		pub fn bid(origin: OriginFor<T>, file_id: T::Hash, amount: BalanceOf<T>) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			let mut auction = Self::auctions(&file_id).ok_or(<Error<T>>::AuctionNotFound)?;
+			ensure!(<frame_system::Pallet<T>>::block_number() < auction.end, <Error<T>>::AuctionEnded);
+			ensure!(bidder != auction.seller, <Error<T>>::CannotBuyOwnFile);
+			ensure!(amount >= auction.reserve, <Error<T>>::BidTooLow);
+
+			if let Some((previous, previous_amount)) = auction.bid.take() {
+				ensure!(amount > previous_amount, <Error<T>>::BidTooLow);
+				T::Currency::unreserve(&previous, previous_amount);
+				if previous != bidder {
+					Self::deposit_event(Event::Outbid(file_id, previous, previous_amount));
+				}
+			}
+			T::Currency::reserve(&bidder, amount)?;
+			auction.bid = Some((bidder.clone(), amount));
+			<Auctions<T>>::insert(&file_id, auction);
+
+			Self::deposit_event(Event::BidPlaced(file_id, bidder, amount));
+			Ok(())
+		}
+
+		// Closes an auction nobody has bid on yet.
+		#[pallet::weight(T::WeightInfo::cancel_auction())]
+		pub fn cancel_auction(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			let seller = ensure_signed(origin)?;
+
+			Self::owned_file(&file_id, &seller)?;
+			let auction = Self::auctions(&file_id).ok_or(<Error<T>>::AuctionNotFound)?;
+			ensure!(auction.bid.is_none(), <Error<T>>::AuctionHasBids);
+
+			Self::cancel_auction_of(&file_id);
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::register_provider())]
+		pub fn register_provider(origin: OriginFor<T>, stake: BalanceOf<T>, capacity: u64, price_per_byte: u64) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+
+			ensure!(!<Providers<T>>::contains_key(&provider), <Error<T>>::ProviderAlreadyRegistered);
+			ensure!(stake >= T::MinProviderStake::get(), <Error<T>>::InsufficientStake);
+
+			T::Currency::reserve(&provider, stake)?;
+
+			<Providers<T>>::insert(&provider, StorageProvider::<T> {
+				stake,
+				capacity,
+				used: 0,
+				price_per_byte,
+				status: ProviderStatus::Active,
+			});
+
+			Self::deposit_event(Event::ProviderRegistered(provider, stake));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::update_provider())]
+		pub fn update_provider(origin: OriginFor<T>, capacity: u64, price_per_byte: u64) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+
+			<Providers<T>>::try_mutate(&provider, |details| -> DispatchResult {
+				let details = details.as_mut().ok_or(<Error<T>>::ProviderNotFound)?;
+				ensure!(details.status == ProviderStatus::Active, <Error<T>>::ProviderNotActive);
+				ensure!(capacity >= details.used, <Error<T>>::CapacityBelowUsage);
+
+				details.capacity = capacity;
+				details.price_per_byte = price_per_byte;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::ProviderUpdated(provider, capacity, price_per_byte));
+			Ok(())
+		}
+
+		// Starts unbonding. The provider is detached from every file it hosts right away, and other
+		// providers are picked to replace it.
+		#[pallet::weight(Pallet::<T>::deregister_provider_weight(T::MaxFilesPerProvider::get()))]
+		pub fn deregister_provider(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let provider = ensure_signed(origin)?;
+
+			let mut details = Self::providers(&provider).ok_or(<Error<T>>::ProviderNotFound)?;
+			ensure!(details.status == ProviderStatus::Active, <Error<T>>::ProviderNotActive);
+
+			let hosted = <ProviderContents<T>>::take(&provider);
+			for content_id in hosted.iter() {
+				Self::detach_provider(content_id, &provider);
+				Self::deposit_event(Event::ContentUnassigned(*content_id, provider.clone()));
+				Self::replicate_content(content_id, Some(&provider));
+			}
+
+			let unbonded_at = <frame_system::Pallet<T>>::block_number().saturating_add(T::UnbondingPeriod::get());
+			details.used = 0;
+			details.status = ProviderStatus::Unbonding(unbonded_at);
+			<Providers<T>>::insert(&provider, details);
+
+			Self::deposit_event(Event::ProviderUnbonding(provider, unbonded_at));
+			Ok(Some(Self::deregister_provider_weight(hosted.len() as u32)).into())
+		}
+
+		#[pallet::weight(T::WeightInfo::withdraw_stake())]
+		pub fn withdraw_stake(origin: OriginFor<T>) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+
+			let details = Self::providers(&provider).ok_or(<Error<T>>::ProviderNotFound)?;
+			match details.status {
+				ProviderStatus::Unbonding(unbonded_at) => ensure!(
+					<frame_system::Pallet<T>>::block_number() >= unbonded_at,
+					<Error<T>>::ProviderStillUnbonding
+				),
+				ProviderStatus::Active => Err(<Error<T>>::ProviderNotUnbonding)?,
+			}
+
+			ensure!(!<Challenges<T>>::contains_key(&provider), <Error<T>>::ChallengePending);
+
+			T::Currency::unreserve(&provider, details.stake);
+			<Providers<T>>::remove(&provider);
+
+			Self::deposit_event(Event::ProviderDeregistered(provider, details.stake));
+			Ok(())
+		}
+
+		// Asks `provider` to store the contents of the file. Providers only host contents they agreed
+		// to, so the provider is assigned once it accepts the request with `fulfill_replication`.
+		// Unlike the orders opened by `replicate`, the request carries no reward.
+		#[pallet::weight(T::WeightInfo::assign_provider())]
+		pub fn assign_provider(origin: OriginFor<T>, file_id: T::Hash, provider: T::AccountId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			let content_id = file.content_id();
+			Self::ensure_can_host(&content_id, &provider)?;
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut orders = <ReplicationOrders<T>>::get(&content_id);
+			if let Some(index) = orders.iter().position(|order| order.provider == provider) {
+				ensure!(orders[index].expires_at < now, <Error<T>>::ProviderAlreadyRequested);
+				// A lapsed order is replaced, and its reward goes back to the storage fund.
+				let lapsed = orders.swap_remove(index);
+				if !lapsed.reward.is_zero() {
+					<StorageFunds<T>>::mutate(&content_id, |fund| *fund = fund.saturating_add(lapsed.reward));
+				}
+			}
+			let pending = <ContentProviders<T>>::get(&content_id).len().saturating_add(orders.len());
+			ensure!(pending < T::MaxProvidersPerFile::get() as usize, <Error<T>>::ExceedMaxProvidersPerFile);
+
+			let expires_at = now.saturating_add(T::ReplicationPeriod::get());
+			let order = ReplicationOrder::<T> { provider: provider.clone(), reward: Zero::zero(), expires_at };
+			orders.try_push(order).map_err(|_| <Error<T>>::ExceedMaxProvidersPerFile)?;
+			<ReplicationOrders<T>>::insert(&content_id, orders);
+
+			Self::deposit_event(Event::ReplicationOrderOpened(content_id, provider, Zero::zero(), expires_at));
+			Ok(())
+		}
+
+		// Either the file owner or the provider itself can end an assignment. A provider leaving a
+		// file has another provider picked to replace it.
+		#[pallet::weight(T::WeightInfo::unassign_provider().saturating_add(T::WeightInfo::replicate()))]
+		pub fn unassign_provider(origin: OriginFor<T>, file_id: T::Hash, provider: T::AccountId) -> DispatchResult {
+			let signer = ensure_signed(origin)?;
+
+			let file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			ensure!(signer == file.owner || signer == provider, <Error<T>>::InvalidSigner);
+
+			ensure!(Self::remove_assignment(&file.content_id(), &provider), <Error<T>>::ProviderNotAssigned);
+
+			Self::deposit_event(Event::ContentUnassigned(file.content_id(), provider.clone()));
+			if signer == provider {
+				Self::replicate_content(&file.content_id(), Some(&provider));
+			}
+			Ok(())
+		}
+
+		// Makes new contents the current version of the file. The previous version is kept in the
+		// file's history, and counts towards the owner's quota until the file is removed or the
+		// version is dropped as the oldest of a full history.
+		#[pallet::weight(T::WeightInfo::publish_version())]
+		#[transactional]
+		pub fn publish_version(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			file_link: [u8; 20],
+			file_size: u64,
+			content_root: H256,
+			chunk_size: u32,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			let next = FileVersion { version: 0, file_link, file_size, content_root, chunk_size };
+			Self::do_publish_version(&file_id, file, next, &owner)
+		}
+
+		// Makes a previous version the current one again. The version it replaces takes its place
+		// in the history.
+		#[pallet::weight(T::WeightInfo::rollback_to_version())]
+		pub fn rollback_to_version(origin: OriginFor<T>, file_id: T::Hash, version: u32) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_rollback(&file_id, file, version)
+		}
+
+		// Stores the key of an encrypted file for `recipient`, replacing any key stored before, and
+		// settles the recipient's pending key request. The owner encrypts the key under the
+		// recipient's public key off chain.
+		#[pallet::weight(T::WeightInfo::grant_access())]
+		pub fn grant_access(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			recipient: T::AccountId,
+			envelope: BoundedVec<u8, T::MaxEnvelopeLength>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let file = Self::owned_file(&file_id, &owner)?;
+			Self::do_grant_access(&file_id, &file, &recipient, envelope)
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_access())]
+		pub fn revoke_access(origin: OriginFor<T>, file_id: T::Hash, recipient: T::AccountId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			Self::owned_file(&file_id, &owner)?;
+			Self::do_revoke_access(&file_id, recipient)
+		}
+
+		// Sets the key the caller's offchain worker signs key deliveries with. The node must hold
+		// it in its keystore under `KEY_TYPE`.
+		#[pallet::weight(T::WeightInfo::set_delivery_key())]
+		pub fn set_delivery_key(origin: OriginFor<T>, key: sr25519::Public) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			<DeliveryKeys<T>>::insert(&owner, key);
+
+			Self::deposit_event(Event::DeliveryKeySet(owner));
+			Ok(())
+		}
+
+		// Submitted by the owner's offchain worker with the file key encrypted for the buyer.
+		#[pallet::weight(T::WeightInfo::fulfill_key_request())]
+		pub fn fulfill_key_request(
+			origin: OriginFor<T>,
+			file_id: T::Hash,
+			buyer: T::AccountId,
+			envelope: BoundedVec<u8, T::MaxEnvelopeLength>,
+			signature: sr25519::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			ensure!(<KeyRequests<T>>::contains_key(&file_id, &buyer), <Error<T>>::KeyRequestNotFound);
+			ensure!(
+				Self::is_valid_key_delivery(&file_id, &buyer, &envelope, &signature),
+				<Error<T>>::InvalidKeyDelivery
+			);
+
+			Self::store_envelope(&file_id, &buyer, envelope)
+		}
+
+		// Adds, changes or, when `plan` is `None`, withdraws a plan accounts can buy.
+		#[pallet::weight(T::WeightInfo::set_plan())]
+		pub fn set_plan(origin: OriginFor<T>, plan_id: u32, plan: Option<QuotaPlan<BalanceOf<T>>>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			<QuotaPlans<T>>::set(plan_id, plan);
+
+			Self::deposit_event(Event::PlanUpdated(plan_id));
+			Ok(())
+		}
+
+		// Allows, or stops allowing, a key to sign availability reports. The reporting node must hold
+		// it in its keystore under `KEY_TYPE`.
+		#[pallet::weight(T::WeightInfo::set_reporter())]
+		pub fn set_reporter(origin: OriginFor<T>, key: sr25519::Public, allowed: bool) -> DispatchResult {
+			ensure_root(origin)?;
+
+			if allowed {
+				<AvailabilityReporters<T>>::insert(&key, ());
+			} else {
+				<AvailabilityReporters<T>>::remove(&key);
+			}
+
+			Self::deposit_event(Event::ReporterSet(key, allowed));
+			Ok(())
+		}
+
+		// Pays the accountant for a plan and adds its bytes to the caller's quota for good.
+		#[pallet::weight(T::WeightInfo::buy_plan())]
+		pub fn buy_plan(origin: OriginFor<T>, plan_id: u32) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+
+			let plan = Self::quota_plans(plan_id).ok_or(<Error<T>>::PlanNotFound)?;
+			let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+
+			T::Currency::transfer(&buyer, &accountant, plan.price, ExistenceRequirement::KeepAlive)?;
+			<ExtraQuota<T>>::mutate(&buyer, |extra| *extra = extra.saturating_add(plan.bytes));
+
+			Self::deposit_event(Event::PlanBought(buyer.clone(), plan_id, Self::quota(&buyer)));
+			Ok(())
+		}
+
+		// Pays the rent of the file for `blocks` more blocks, counted from the block it is paid
+		// through. Rent goes to the accountant. Any co-owner can pay the rent of a co-owned file.
+		#[pallet::weight(T::WeightInfo::extend_storage())]
+		pub fn extend_storage(origin: OriginFor<T>, file_id: T::Hash, blocks: T::BlockNumber) -> DispatchResult {
+			let payer = ensure_signed(origin)?;
+
+			let file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			let co_owner = Self::co_owners(&file_id).map_or(false, |co_ownership| co_ownership.owners.contains(&payer));
+			ensure!(file.owner == payer || co_owner, <Error<T>>::InvalidSigner);
+			let mut rent = Self::file_rent(&file_id).ok_or(<Error<T>>::RentNotRequired)?;
+			let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+
+			let rent_cost = Self::rent_cost(Self::stored_bytes(&file_id, &file), blocks);
+			T::Currency::transfer(&payer, &accountant, rent_cost, ExistenceRequirement::KeepAlive)?;
+
+			rent.paid_through = rent.paid_through.saturating_add(blocks);
+			if rent.paid_through >= <frame_system::Pallet<T>>::block_number() {
+				rent.expired = false;
+			}
+			<FileRent<T>>::insert(&file_id, rent);
+
+			Self::deposit_event(Event::StorageExtended(file_id, rent.paid_through));
+			Ok(())
+		}
+
+		// Adds to the fund paying providers that replicate the contents of the file. Anyone can
+		// contribute.
+		#[pallet::weight(T::WeightInfo::fund_file())]
+		pub fn fund_file(origin: OriginFor<T>, file_id: T::Hash, amount: BalanceOf<T>) -> DispatchResult {
+			let funder = ensure_signed(origin)?;
+
+			let file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+
+			T::Currency::transfer(&funder, &Self::account_id(), amount, ExistenceRequirement::KeepAlive)?;
+			<StorageFunds<T>>::mutate(&file.content_id(), |fund| *fund = fund.saturating_add(amount));
+
+			Self::deposit_event(Event::FileFunded(file_id, funder, amount));
+			Ok(())
+		}
+
+		// Drops expired replication orders and picks providers for the copies the contents of the
+		// file are missing. Anyone can call it.
+		#[pallet::weight(T::WeightInfo::replicate())]
+		pub fn replicate(origin: OriginFor<T>, file_id: T::Hash) -> DispatchResult {
+			ensure_signed(origin)?;
 
 			let file = Self::get_file_details(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+			Self::replicate_content(&file.content_id(), None);
+
+			Ok(())
+		}
+
+		// Called by a provider picked in a replication order once it stores the contents. The
+		// provider is assigned to the contents and paid the order's reward.
+		#[pallet::weight(T::WeightInfo::fulfill_replication())]
+		#[transactional]
+		pub fn fulfill_replication(origin: OriginFor<T>, content_id: H256) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+
+			let mut orders = <ReplicationOrders<T>>::get(&content_id);
+			let index = orders
+				.iter()
+				.position(|order| order.provider == provider)
+				.ok_or(<Error<T>>::ReplicationOrderNotFound)?;
+			let order = orders.swap_remove(index);
+			ensure!(
+				order.expires_at >= <frame_system::Pallet<T>>::block_number(),
+				<Error<T>>::ReplicationOrderExpired
+			);
+
+			Self::attach_provider(&content_id, &provider)?;
+			T::Currency::transfer(&Self::account_id(), &provider, order.reward, ExistenceRequirement::AllowDeath)?;
+
+			if orders.is_empty() {
+				<ReplicationOrders<T>>::remove(&content_id);
+			} else {
+				<ReplicationOrders<T>>::insert(&content_id, orders);
+			}
+
+			Self::deposit_event(Event::ContentAssigned(content_id, provider.clone()));
+			Self::deposit_event(Event::ReplicationFulfilled(content_id, provider, order.reward));
+			Ok(())
+		}
+
+		// Answers the caller's open challenge with the requested chunk and its Merkle proof. An
+		// invalid proof is not an error: the provider is slashed and the challenge is closed.
+		#[pallet::weight(
+			T::WeightInfo::respond_to_challenge(chunk.len() as u32).saturating_add(T::WeightInfo::replicate())
+		)]
+		pub fn respond_to_challenge(
+			origin: OriginFor<T>,
+			chunk: BoundedVec<u8, T::MaxChunkSize>,
+			proof: BoundedVec<H256, T::MaxProofLength>,
+		) -> DispatchResult {
+			let provider = ensure_signed(origin)?;
+
+			let challenge = <Challenges<T>>::take(&provider).ok_or(<Error<T>>::ChallengeNotFound)?;
+
+			let valid = Self::contents(&challenge.content_id).map_or(false, |content| {
+				content.verify_chunk(challenge.chunk_index, &chunk, &proof)
+			});
+
+			if valid {
+				Self::deposit_event(Event::ChallengePassed(provider, challenge.content_id));
+			} else {
+				Self::deposit_event(Event::ChallengeFailed(provider.clone(), challenge.content_id));
+				Self::slash_provider(&provider, &challenge.content_id);
+			}
+
+			Ok(())
+		}
+
+		// Submitted by offchain workers with the sampled files whose links could not be fetched,
+		// signed with an authorised reporter key.
+		#[pallet::weight(T::WeightInfo::report_unavailable(files.len() as u32))]
+		pub fn report_unavailable(
+			origin: OriginFor<T>,
+			block_number: T::BlockNumber,
+			files: BoundedVec<T::Hash, T::MaxAvailabilityChecks>,
+			reporter: sr25519::Public,
+			signature: sr25519::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			ensure!(
+				Self::is_valid_report(&block_number, &files, &reporter, &signature),
+				<Error<T>>::InvalidReport
+			);
+
+			<NextAvailabilityReport<T>>::put(block_number.saturating_add(T::AvailabilityCheckInterval::get()));
+
+			for file_id in files {
+				// The file may have been removed since it was checked.
+				if <Files<T>>::contains_key(&file_id) {
+					Self::deposit_event(Event::FileUnavailable(file_id));
+				}
+			}
+
+			Ok(())
+		}
+    }
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			match call {
+				Call::report_unavailable { block_number, files, reporter, signature } =>
+					Self::validate_report(block_number, files, reporter, signature),
+				Call::fulfill_key_request { file_id, buyer, envelope, signature } =>
+					Self::validate_key_delivery(file_id, buyer, envelope, signature),
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
+	}
+
+
+    impl<T: Config> Pallet<T> {
+		// Accepts one report per availability check, for a check that is neither stale nor in the
+		// future, signed by an authorised reporter.
+		fn validate_report(
+			block_number: &T::BlockNumber,
+			files: &[T::Hash],
+			reporter: &sr25519::Public,
+			signature: &sr25519::Signature,
+		) -> TransactionValidity {
+			if files.is_empty() {
+				return InvalidTransaction::Call.into()
+			}
+			if *block_number < Self::next_availability_report() {
+				return InvalidTransaction::Stale.into()
+			}
+			if *block_number > <frame_system::Pallet<T>>::block_number() {
+				return InvalidTransaction::Future.into()
+			}
+			if !Self::is_valid_report(block_number, files, reporter, signature) {
+				return InvalidTransaction::BadProof.into()
+			}
+
+			ValidTransaction::with_tag_prefix("MydropboxAvailability")
+				.priority(T::UnsignedPriority::get())
+				.and_provides(block_number)
+				.longevity(T::AvailabilityCheckInterval::get().saturated_into::<u64>())
+				.propagate(true)
+				.build()
+		}
+
+		// Accepts one delivery per open key request, signed with the delivery key of the file owner.
+		fn validate_key_delivery(
+			file_id: &T::Hash,
+			buyer: &T::AccountId,
+			envelope: &[u8],
+			signature: &sr25519::Signature,
+		) -> TransactionValidity {
+			let request = match Self::key_requests(file_id, buyer) {
+				Some(request) => request,
+				None => return InvalidTransaction::Stale.into(),
+			};
+			if !Self::is_valid_key_delivery(file_id, buyer, envelope, signature) {
+				return InvalidTransaction::BadProof.into()
+			}
+
+			let remaining = request.deadline.saturating_sub(<frame_system::Pallet<T>>::block_number());
+			ValidTransaction::with_tag_prefix("MydropboxKeyDelivery")
+				.priority(T::UnsignedPriority::get())
+				.and_provides((file_id, buyer))
+				.longevity(remaining.saturated_into::<u64>().max(1))
+				.propagate(true)
+				.build()
+		}
+
+		/// Worst-case weight of `download_file` over every pricing branch, used before the file
+		/// type is known.
+		fn download_file_weight(downloaded: u32) -> Weight {
+			T::WeightInfo::download_file_normal(downloaded)
+				.max(T::WeightInfo::download_file_normal_oversized(downloaded))
+				.max(T::WeightInfo::download_file_privileged(downloaded))
+				.max(T::WeightInfo::download_file_encrypted(downloaded))
+				.max(T::WeightInfo::upgrade_license(downloaded))
+		}
+
+		/// Returns the file if `who` owns it.
+		fn owned_file(file_id: &T::Hash, who: &T::AccountId) -> Result<File<T>, DispatchError> {
+			let file = Self::get_file_details(file_id).ok_or(<Error<T>>::FileNotFound)?;
+			ensure!(file.owner == *who, <Error<T>>::InvalidSigner);
+			Ok(file)
+		}
+
+		/// Moves the file between the owner lists and quotas, stores it with its new owner and
+		/// hands its NFT over.
+		fn move_file(file_id: &T::Hash, file: &mut File<T>, new_owner: &T::AccountId) -> DispatchResult {
+			let owner = file.owner.clone();
+
+			// Both owner lists are updated in memory first, so nothing is written unless the new
+			// owner has room for the file.
+			let mut owned = <FilesPerUser<T>>::get(&owner);
+			let index = owned.iter().position(|id| id == file_id).ok_or(<Error<T>>::FileDoesntExist)?;
+
+			let moved = *new_owner != owner;
+			if moved {
+				let stored_bytes = Self::stored_bytes(file_id, file);
+				let mut received = <FilesPerUser<T>>::get(new_owner);
+				received.try_push(*file_id).map_err(|_| <Error<T>>::ExceedMaxFileUploaded)?;
+				let received_bytes = Self::bytes_used_with(new_owner, stored_bytes)?;
+				owned.swap_remove(index);
+
+				<FilesPerUser<T>>::insert(&owner, owned);
+				<FilesPerUser<T>>::insert(new_owner, received);
+				<BytesUsed<T>>::mutate(&owner, |used| *used = used.saturating_sub(stored_bytes));
+				<BytesUsed<T>>::insert(new_owner, received_bytes);
+
+				// Listings, auctions and transfer offers are made by the previous owner.
+				if <Listings<T>>::take(file_id).is_some() {
+					Self::deposit_event(Event::Delisted(*file_id));
+				}
+				Self::remove_transfer_offer(file_id);
+				Self::cancel_auction_of(file_id);
+				// Co-owners only manage the file while their shared account owns it.
+				<CoOwners<T>>::remove(file_id);
+				<Proposals<T>>::drain_prefix(file_id).for_each(drop);
+			}
+
+			file.owner = new_owner.clone();
+			<Files<T>>::insert(file_id, file.clone());
+			// The NFT is only unlocked once the file has a new owner, see the `Locker` impl. The NFT
+			// pallet lets its holder burn it, and the new owner then gets a new one.
+			if moved {
+				let collection = T::NftCollection::get();
+				if T::Nfts::owner(&collection, file_id).is_some() {
+					T::Nfts::transfer(&collection, file_id, new_owner)?;
+				} else {
+					Self::mint_nft(file_id, new_owner)?;
+				}
+			}
+			Ok(())
+		}
+
+		/// Hands a paid-for file and its NFT over to `buyer`.
+		fn sell_file(file_id: &T::Hash, file: &mut File<T>, buyer: &T::AccountId, price: BalanceOf<T>) -> DispatchResult {
+			let seller = file.owner.clone();
+			Self::move_file(file_id, file, buyer)?;
+
+			Self::deposit_event(Event::Sold(*file_id, seller, buyer.clone(), price));
+			Ok(())
+		}
+
+		/// Charges `signer` for a version of the file at the price of `tier`, and records the download
+		/// along with the license bought. Returns the weight of the pricing branch taken.
+		fn do_download(
+			signer: &T::AccountId,
+			file_id: &T::Hash,
+			version: Option<u32>,
+			tier: LicenseTier,
+		) -> Result<Weight, DispatchError> {
+			let file = Self::get_file_details(file_id).ok_or(<Error<T>>::FileNotFound)?;
+
+			ensure!(file.allow_download, <Error<T>>::FileNotAllowedToDownload);
+			ensure!(!Self::rent_overdue(file_id), <Error<T>>::StorageExpired);
+
+			let file = match version {
+				Some(version) if version != file.version => {
+					let previous = <FileVersions<T>>::get(file_id)
+						.into_iter()
+						.find(|previous| previous.version == version)
+						.ok_or(<Error<T>>::VersionNotFound)?;
+					file.with_version(previous)
+				},
+				_ => file,
+			};
+
+			let mut downloads = <FileDownloads<T>>::get(file_id, file.version);
+			let license_key = (signer.clone(), file.version);
+
+			// Downloaders are kept sorted so the lookup and the insertion below agree. A license with
+			// no fetches left is renewed by buying the version again, without a new download.
+			let download_index = match downloads.binary_search(signer) {
+				Err(index) => Some(index),
+				Ok(_) => match Self::licenses(file_id, &license_key) {
+					Some(license) if license.fetches == Some(0) => None,
+					Some(license) if license.tier == LicenseTier::Personal && tier == LicenseTier::Commercial => {
+						Self::upgrade_license(signer, file_id, &file, license)?;
+						return Ok(T::WeightInfo::upgrade_license(downloads.len() as u32))
+					},
+					_ => Err(<Error<T>>::AlreadyDownloaded)?,
+				},
+			};
+
+			// Buyers without the key of an encrypted file only pay once the owner delivers it.
+			let needs_key = file.file_type == FileType::Encrypted && !<KeyEnvelopes<T>>::contains_key(file_id, signer);
+			ensure!(!needs_key || !<KeyRequests<T>>::contains_key(file_id, signer), <Error<T>>::KeyRequestPending);
+
+			let downloaded = downloads.len() as u32;
+			let actual_weight = match file.file_type {
+				_ if needs_key => T::WeightInfo::download_file_encrypted(downloaded),
+				FileType::Normal | FileType::Encrypted if file.file_size > 250 => T::WeightInfo::download_file_normal_oversized(downloaded),
+				FileType::Normal | FileType::Encrypted => T::WeightInfo::download_file_normal(downloaded),
+				FileType::Privileged => T::WeightInfo::download_file_privileged(downloaded),
+			};
+
+			// Every check happens before the payment, so a failed download never moves funds.
+			let cost = Self::download_cost(&file, tier)?;
+			ensure!(T::Currency::free_balance(signer) >= cost, <Error<T>>::NotEnoughBalance);
+			let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+
+			let mut downloads_count = Self::all_downloads_count();
+			if let Some(download_index) = download_index {
+				downloads.try_insert(download_index, signer.clone())
+				.map_err(|_| <Error<T>>::ExceedMaxFileDownload)?;
+				downloads_count = downloads_count.checked_add(1).ok_or(<Error<T>>::FileDownloadCountOverflow)?;
+			}
+
+			if needs_key {
+				Self::request_key(file_id, signer, file.version, cost)?;
+			} else {
+				// Transfer the amount to the accountant
+				T::Currency::transfer(signer, &accountant, cost, ExistenceRequirement::KeepAlive)?;
+			}
+
+			<FileDownloads<T>>::insert(file_id, file.version, downloads);
+			<AllDownloadsCount<T>>::put(downloads_count);
+			<Licenses<T>>::insert(file_id, license_key, License {
+				version: file.version,
+				tier,
+				kind: file.license.kind,
+				terms_hash: file.license.terms_hash,
+				fetches: T::FetchesPerLicense::get(),
+			});
+
+			Self::deposit_event(Event::Downloaded(signer.clone(), *file_id, file.version, cost));
+
+			Ok(actual_weight)
+		}
+
+		/// Charges `holder` the difference between the commercial and personal prices of the version
+		/// licensed, and moves the license to commercial use under the current terms.
+		fn upgrade_license(holder: &T::AccountId, file_id: &T::Hash, file: &File<T>, mut license: License) -> DispatchResult {
+			ensure!(!<KeyRequests<T>>::contains_key(file_id, holder), <Error<T>>::KeyRequestPending);
+			let cost = Self::download_cost(file, LicenseTier::Commercial)?
+				.saturating_sub(Self::download_cost(file, LicenseTier::Personal)?);
+			let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+			T::Currency::transfer(holder, &accountant, cost, ExistenceRequirement::KeepAlive)?;
+
+			license.tier = LicenseTier::Commercial;
+			license.kind = file.license.kind;
+			license.terms_hash = file.license.terms_hash;
+			<Licenses<T>>::insert(file_id, (holder.clone(), file.version), license);
+
+			Self::deposit_event(Event::LicenseUpgraded(*file_id, holder.clone(), file.version, cost));
+			Ok(())
+		}
+
+		/// Stores a new file for its owner, charges the upload fee and mints its NFT.
+		fn do_upload(file: File<T>) -> Result<T::Hash, DispatchError> {
+			let sender = file.owner.clone();
+
+			// Chunks must fit in a challenge response.
+			ensure!(file.chunk_size > 0 && file.chunk_size <= T::MaxChunkSize::get(), <Error<T>>::InvalidChunkSize);
+			ensure!(
+				file.replication_factor > 0 && file.replication_factor <= T::MaxProvidersPerFile::get(),
+				<Error<T>>::InvalidReplicationFactor
+			);
+
+			let file_id = T::Hashing::hash_of(&file);
+			ensure!(!<Files<T>>::contains_key(&file_id), <Error<T>>::FileAlreadyExists);
+			let new_count = Self::all_files_count().checked_add(1).ok_or(<Error<T>>::FileCountOverflow)?;
+			let bytes_used = Self::bytes_used_with(&sender, file.file_size)?;
+
+			// The first rent period is paid up front, so the file can be downloaded right away.
+			let now = <frame_system::Pallet<T>>::block_number();
+			let rent_cost = Self::rent_cost(file.file_size, T::InitialRentPeriod::get());
+			if !rent_cost.is_zero() {
+				let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+				T::Currency::transfer(&sender, &accountant, rent_cost, ExistenceRequirement::KeepAlive)?;
+			}
+
+			<FilesPerUser<T>>::try_mutate(&sender, |file_vec| file_vec.try_push(file_id))
+			.map_err(|_| <Error<T>>::ExceedMaxFileUploaded)?;
+
+			let refs = Self::add_content_ref(&sender, &file.current_version(), file.replication_factor)?;
+			Self::mint_nft(&file_id, &sender)?;
+			let content_id = file.content_id();
+			<Files<T>>::insert(file_id, file);
+			<AllFilesCount<T>>::put(new_count);
+			<BytesUsed<T>>::insert(&sender, bytes_used);
+
+			if !T::RentPerByte::get().is_zero() {
+				let paid_through = now.saturating_add(T::InitialRentPeriod::get());
+				<FileRent<T>>::insert(file_id, RentStatus { paid_through, expired: false });
+			}
+
+			Self::deposit_event(Event::Uploaded(sender, file_id));
+			if refs > 1 {
+				Self::deposit_event(Event::ContentShared(file_id, content_id, refs));
+			}
+
+			Ok(file_id)
+		}
+
+		/// Pays out `amount` earned by the owner of a file through `pay`: to its co-owners in equal
+		/// shares, the remainder going to the first one, or to the owner alone.
+		fn pay_owners(
+			file_id: &T::Hash,
+			owner: &T::AccountId,
+			amount: BalanceOf<T>,
+			mut pay: impl FnMut(&T::AccountId, BalanceOf<T>) -> DispatchResult,
+		) -> DispatchResult {
+			let owners = match Self::co_owners(file_id) {
+				Some(co_ownership) => co_ownership.owners,
+				None => return pay(owner, amount),
+			};
+
+			let count: BalanceOf<T> = (owners.len() as u32).into();
+			let share = amount / count;
+			let mut remainder = amount.saturating_sub(share.saturating_mul(count));
+			for co_owner in owners.iter() {
+				pay(co_owner, share.saturating_add(remainder))?;
+				remainder = Zero::zero();
+			}
+			Ok(())
+		}
+
+		/// Offers the file to `new_owner`, who takes it over with `accept_transfer`.
+		fn do_transfer(owner: &T::AccountId, file_id: &T::Hash, new_owner: T::AccountId) -> DispatchResult {
+			Self::owned_file(file_id, owner)?;
+			ensure!(new_owner != *owner, <Error<T>>::InvalidOperation);
+			ensure!(!<Auctions<T>>::contains_key(file_id), <Error<T>>::AuctionInProgress);
+
+			// A replaced offer gives its place in its deadline block back.
+			Self::remove_transfer_offer(file_id);
+			let deadline = <frame_system::Pallet<T>>::block_number().saturating_add(T::TransferOfferTimeout::get());
+			let expires_at = deadline.saturating_add(One::one());
+			let mut expiring = <TransferOfferDeadlines<T>>::get(expires_at);
+			expiring.try_push(*file_id).map_err(|_| <Error<T>>::TooManyTransferOffers)?;
+			<TransferOfferDeadlines<T>>::insert(expires_at, expiring);
+			<PendingTransfers<T>>::insert(file_id, PendingTransfer::<T> { from: owner.clone(), to: new_owner.clone(), deadline });
+
+			Self::deposit_event(Event::TransferOffered(*file_id, owner.clone(), new_owner, deadline));
+			Ok(())
+		}
+
+		fn do_accept_offer(file_id: &T::Hash, mut file: File<T>, bidder: T::AccountId) -> DispatchResult {
+			let owner = file.owner.clone();
+			ensure!(bidder != owner, <Error<T>>::CannotBuyOwnFile);
+			ensure!(!<Auctions<T>>::contains_key(file_id), <Error<T>>::AuctionInProgress);
+
+			let mut offers = Self::offers(file_id);
+			let index = offers.iter().position(|(who, _)| *who == bidder).ok_or(<Error<T>>::OfferNotFound)?;
+			let (_, amount) = offers.remove(index);
+			<Offers<T>>::insert(file_id, offers);
+
+			Self::pay_owners(file_id, &owner, amount, |to, share| {
+				T::Currency::repatriate_reserved(&bidder, to, share, BalanceStatus::Free).map(|_| ())
+			})?;
+			Self::sell_file(file_id, &mut file, &bidder, amount)
+		}
+
+		fn do_create_auction(
+			file_id: &T::Hash,
+			seller: T::AccountId,
+			reserve: BalanceOf<T>,
+			end: T::BlockNumber,
+		) -> DispatchResult {
+			ensure!(!<Auctions<T>>::contains_key(file_id), <Error<T>>::AuctionInProgress);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(end > now, <Error<T>>::InvalidAuctionEnd);
+			ensure!(end <= now.saturating_add(T::MaxAuctionDuration::get()), <Error<T>>::AuctionTooLong);
+
+			let mut ending = <AuctionEnds<T>>::get(end);
+			ending.try_push(*file_id).map_err(|_| <Error<T>>::TooManyAuctions)?;
+			<AuctionEnds<T>>::insert(end, ending);
+			<Auctions<T>>::insert(file_id, Auction::<T> { seller: seller.clone(), reserve, end, bid: None });
+			if <Listings<T>>::take(file_id).is_some() {
+				Self::deposit_event(Event::Delisted(*file_id));
+			}
+
+			Self::deposit_event(Event::AuctionCreated(*file_id, seller, reserve, end));
+			Ok(())
+		}
+
+		/// Hands the file over to its shared account, or replaces the co-owners of a file the
+		/// shared account already owns. Proposals of the previous co-owners are dropped, but their
+		/// ids are not reused.
+		fn do_set_co_owners(
+			file_id: &T::Hash,
+			mut file: File<T>,
+			owners: BoundedVec<T::AccountId, T::MaxCoOwners>,
+			threshold: u32,
+		) -> DispatchResult {
+			ensure!(threshold > 0 && threshold as usize <= owners.len(), <Error<T>>::InvalidThreshold);
+			let mut distinct = owners.to_vec();
+			distinct.sort();
+			distinct.dedup();
+			ensure!(distinct.len() == owners.len(), <Error<T>>::DuplicateCoOwner);
+			ensure!(!<Auctions<T>>::contains_key(file_id), <Error<T>>::AuctionInProgress);
+
+			let shared = Self::co_owner_account(file_id);
+			Self::move_file(file_id, &mut file, &shared)?;
+			let next_proposal_id = Self::co_owners(file_id).map_or(0, |co_ownership| co_ownership.next_proposal_id);
+			// Bounded by `MaxProposals`.
+			<Proposals<T>>::drain_prefix(file_id).for_each(drop);
+			<CoOwners<T>>::insert(file_id, CoOwnership::<T> { owners, threshold, next_proposal_id, open_proposals: 0 });
+
+			Self::deposit_event(Event::CoOwnersSet(*file_id, shared, threshold));
+			Ok(())
+		}
+
+		fn do_update_file(file_id: &T::Hash, mut file: File<T>, cost: u64, allow_download: bool) {
+			file.cost = cost;
+			file.allow_download = allow_download;
+			<Files<T>>::insert(file_id, file);
+
+			Self::deposit_event(Event::FileUpdated(*file_id, cost, allow_download));
+		}
+
+		fn do_set_license(file_id: &T::Hash, mut file: File<T>, license: LicenseTerms) -> DispatchResult {
+			// Custom terms mean nothing without the terms themselves.
+			ensure!(license.kind != LicenseKind::Custom || license.terms_hash.is_some(), <Error<T>>::InvalidLicense);
+			file.license = license;
+			<Files<T>>::insert(file_id, file);
+
+			Self::deposit_event(Event::LicenseSet(*file_id, license));
+			Ok(())
+		}
+
+		/// Makes `next` the current version of the file under the next version number, keeping
+		/// the current one in its history. `payer` pays the upload fee of the new contents.
+		fn do_publish_version(file_id: &T::Hash, file: File<T>, next: FileVersion, payer: &T::AccountId) -> DispatchResult {
+			ensure!(next.chunk_size > 0 && next.chunk_size <= T::MaxChunkSize::get(), <Error<T>>::InvalidChunkSize);
+
+			let mut history = <FileVersions<T>>::get(file_id);
+			let version = history
+				.iter()
+				.map(|previous| previous.version)
+				.fold(file.version, u32::max)
+				.checked_add(1)
+				.ok_or(<Error<T>>::TooManyVersions)?;
+			// A full history makes room by dropping its oldest version.
+			let oldest = history
+				.iter()
+				.enumerate()
+				.min_by_key(|(_, previous)| previous.version)
+				.map(|(index, _)| index)
+				.filter(|_| history.len() as u32 >= T::MaxVersions::get());
+			let dropped = oldest.map(|index| history.remove(index));
+			history.try_push(file.current_version()).map_err(|_| <Error<T>>::TooManyVersions)?;
+			let freed = dropped.as_ref().map_or(0, |dropped| dropped.file_size);
+			let bytes_used = Self::bytes_used(&file.owner)
+				.saturating_sub(freed)
+				.checked_add(next.file_size)
+				.filter(|used| *used <= Self::quota(&file.owner))
+				.ok_or(<Error<T>>::QuotaExceeded)?;
+
+			let next = FileVersion { version, ..next };
+			// The new reference is added first, so contents shared with the dropped version stay.
+			let refs = Self::add_content_ref(payer, &next, file.replication_factor)?;
+			if let Some(dropped) = dropped {
+				Self::drop_version(file_id, &dropped);
+			}
+
+			let content_id = next.content_id();
+			<BytesUsed<T>>::insert(&file.owner, bytes_used);
+			<Files<T>>::insert(file_id, file.with_version(next));
+			<FileVersions<T>>::insert(file_id, history);
+
+			Self::deposit_event(Event::VersionPublished(*file_id, version));
+			if refs > 1 {
+				Self::deposit_event(Event::ContentShared(*file_id, content_id, refs));
+			}
+			Ok(())
+		}
+
+		/// Forgets a version dropped from the history of a file, along with its downloads, licenses
+		/// and pending key requests, and releases its contents. The caller accounts for its bytes.
+		fn drop_version(file_id: &T::Hash, dropped: &FileVersion) {
+			let downloads = <FileDownloads<T>>::take(file_id, dropped.version);
+			<AllDownloadsCount<T>>::mutate(|count| *count = count.saturating_sub(downloads.len() as u64));
+			// Licenses and key requests of the version all belong to its downloaders, so this is
+			// bounded by `MaxFilesUploaded`.
+			for buyer in downloads {
+				<Licenses<T>>::remove(file_id, (buyer.clone(), dropped.version));
+				match Self::key_requests(file_id, &buyer) {
+					Some(request) if request.version == dropped.version => {
+						<KeyRequests<T>>::remove(file_id, &buyer);
+						Self::refund_key_request(file_id, &buyer, request.amount);
+					},
+					_ => {},
+				}
+			}
+			Self::release_content(&dropped.content_id());
+
+			Self::deposit_event(Event::VersionDropped(*file_id, dropped.version));
+		}
+
+		fn do_rollback(file_id: &T::Hash, file: File<T>, version: u32) -> DispatchResult {
+			let mut history = <FileVersions<T>>::get(file_id);
+			let previous = history
+				.iter_mut()
+				.find(|previous| previous.version == version)
+				.ok_or(<Error<T>>::VersionNotFound)?;
+			let target = sp_std::mem::replace(previous, file.current_version());
+
+			<Files<T>>::insert(file_id, file.with_version(target));
+			<FileVersions<T>>::insert(file_id, history);
+
+			Self::deposit_event(Event::RolledBack(*file_id, version));
+			Ok(())
+		}
+
+		fn do_grant_access(
+			file_id: &T::Hash,
+			file: &File<T>,
+			recipient: &T::AccountId,
+			envelope: BoundedVec<u8, T::MaxEnvelopeLength>,
+		) -> DispatchResult {
+			ensure!(file.file_type == FileType::Encrypted, <Error<T>>::FileNotEncrypted);
+			Self::store_envelope(file_id, recipient, envelope)
+		}
+
+		fn do_revoke_access(file_id: &T::Hash, recipient: T::AccountId) -> DispatchResult {
+			<KeyEnvelopes<T>>::take(file_id, &recipient).ok_or(<Error<T>>::EnvelopeNotFound)?;
+			<KeyEnvelopeCount<T>>::mutate_exists(file_id, |count| {
+				*count = count.and_then(|count| count.checked_sub(1)).filter(|count| *count > 0)
+			});
+
+			Self::deposit_event(Event::AccessRevoked(*file_id, recipient));
+			Ok(())
+		}
+
+		/// Checks that `operator` may currently make a call allowed by `allowed` for `owner`.
+		fn ensure_operator(
+			owner: &T::AccountId,
+			operator: &T::AccountId,
+			allowed: impl Fn(&OperatorScope) -> bool,
+		) -> DispatchResult {
+			let approval = Self::operators(owner, operator).ok_or(<Error<T>>::NotOperator)?;
+			ensure!(allowed(&approval.scope), <Error<T>>::NotOperator);
+			if let Some(expires_at) = approval.expires_at {
+				ensure!(<frame_system::Pallet<T>>::block_number() <= expires_at, <Error<T>>::OperatorExpired);
+			}
+			Ok(())
+		}
+
+		/// Account owning a file on behalf of its co-owners.
+		pub fn co_owner_account(file_id: &T::Hash) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating((b"team", file_id))
+		}
+
+		/// Stores a proposal, or carries it out once it has `threshold` approvals.
+		fn approve_proposal(file_id: &T::Hash, proposal_id: u32, threshold: u32, proposal: Proposal<T>) -> DispatchResult {
+			if (proposal.approvals.len() as u32) < threshold {
+				<Proposals<T>>::insert(file_id, proposal_id, proposal);
+				return Ok(())
+			}
+
+			Self::close_proposal(file_id, proposal_id);
+			// Approvals start with the proposer.
+			let proposer = proposal.approvals.first().cloned().ok_or(<Error<T>>::ProposalNotFound)?;
+			Self::execute_action(file_id, &proposer, proposal.action)?;
+			Self::deposit_event(Event::ProposalExecuted(*file_id, proposal_id));
+			Ok(())
+		}
+
+		fn close_proposal(file_id: &T::Hash, proposal_id: u32) {
+			<Proposals<T>>::remove(file_id, proposal_id);
+			<CoOwners<T>>::mutate(file_id, |co_ownership| {
+				if let Some(co_ownership) = co_ownership {
+					co_ownership.open_proposals = co_ownership.open_proposals.saturating_sub(1);
+				}
+			});
+		}
+
+		/// Carries out a change approved by the co-owners of a file, on behalf of their shared
+		/// account.
+		fn execute_action(file_id: &T::Hash, proposer: &T::AccountId, action: FileActionOf<T>) -> DispatchResult {
+			let file = Self::get_file_details(file_id).ok_or(<Error<T>>::FileNotFound)?;
+			let shared = file.owner.clone();
+			match action {
+				FileAction::Update(cost, allow_download) => Self::do_update_file(file_id, file, cost, allow_download),
+				FileAction::SetLicense(license) => Self::do_set_license(file_id, file, license)?,
+				FileAction::Transfer(new_owner) => Self::do_transfer(&shared, file_id, new_owner)?,
+				FileAction::List(price) => {
+					<Listings<T>>::insert(file_id, Listing::<T> { seller: shared.clone(), price });
+					Self::deposit_event(Event::Listed(*file_id, shared, price));
+				},
+				FileAction::Delist => {
+					<Listings<T>>::take(file_id).ok_or(<Error<T>>::NotListed)?;
+					Self::deposit_event(Event::Delisted(*file_id));
+				},
+				FileAction::PublishVersion(file_link, file_size, content_root, chunk_size) => {
+					let next = FileVersion { version: 0, file_link, file_size, content_root, chunk_size };
+					Self::do_publish_version(file_id, file, next, proposer)?
+				},
+				FileAction::Rollback(version) => Self::do_rollback(file_id, file, version)?,
+				FileAction::GrantAccess(recipient, envelope) => Self::do_grant_access(file_id, &file, &recipient, envelope)?,
+				FileAction::RevokeAccess(recipient) => Self::do_revoke_access(file_id, recipient)?,
+				FileAction::AcceptOffer(bidder) => Self::do_accept_offer(file_id, file, bidder)?,
+				FileAction::CreateAuction(reserve, end) => Self::do_create_auction(file_id, shared, reserve, end)?,
+				FileAction::SetCoOwners(owners, threshold) => Self::do_set_co_owners(file_id, file, owners, threshold)?,
+			}
+			Ok(())
+		}
+
+		/// Worst-case weight of carrying out a proposal.
+		fn file_action_weight() -> Weight {
+			T::WeightInfo::update_file()
+				.max(T::WeightInfo::set_license())
+				.max(T::WeightInfo::transfer_file())
+				.max(T::WeightInfo::list_file())
+				.max(T::WeightInfo::delist_file())
+				.max(T::WeightInfo::publish_version())
+				.max(T::WeightInfo::rollback_to_version())
+				.max(T::WeightInfo::grant_access())
+				.max(T::WeightInfo::revoke_access())
+				.max(T::WeightInfo::accept_offer(T::MaxFilesUploaded::get()))
+				.max(T::WeightInfo::create_auction())
+				.max(T::WeightInfo::set_co_owners(T::MaxFilesUploaded::get()))
+		}
+
+		/// Account owning the NFT collection of files.
+		pub fn nft_account() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(b"nfts")
+		}
+
+		/// Creates the collection of file NFTs, owned by `nft_account`.
+		pub(crate) fn create_nft_collection() -> DispatchResult {
+			let nft_account = Self::nft_account();
+			T::Nfts::create_collection(&T::NftCollection::get(), &nft_account, &nft_account)
+		}
+
+		/// Mints the NFT of a new file to its owner.
+		pub(crate) fn mint_nft(file_id: &T::Hash, owner: &T::AccountId) -> DispatchResult {
+			T::Nfts::mint_into(&T::NftCollection::get(), file_id, owner)
+		}
+
+		/// Slashes `ChallengeSlash` of the provider's stake. The slashed funds are burned.
+		fn slash_provider(provider: &T::AccountId, content_id: &H256) {
+			<Providers<T>>::mutate(provider, |details| {
+				if let Some(details) = details {
+					let amount = T::ChallengeSlash::get() * details.stake;
+					let (_, unslashed) = T::Currency::slash_reserved(provider, amount);
+					let slashed = amount.saturating_sub(unslashed);
+					details.stake = details.stake.saturating_sub(slashed);
+					Self::deposit_event(Event::ProviderSlashed(provider.clone(), slashed));
+				}
+			});
+
+			// The provider could not prove it holds the contents, so another provider replaces it.
+			if Self::remove_assignment(content_id, provider) {
+				Self::deposit_event(Event::ContentUnassigned(*content_id, provider.clone()));
+				Self::replicate_content(content_id, Some(provider));
+			}
+		}
+
+		/// Bytes `who` can own in total.
+		pub fn quota(who: &T::AccountId) -> u64 {
+			T::DefaultQuota::get().saturating_add(Self::extra_quota(who))
+		}
 
-			ensure!(file.allow_download, <Error<T>>::FileNotAllowedToDownload);
+		// Bytes used by `who` once it owns `file_size` more, if that fits its quota.
+		fn bytes_used_with(who: &T::AccountId, file_size: u64) -> Result<u64, DispatchError> {
+			Self::bytes_used(who)
+				.checked_add(file_size)
+				.filter(|used| *used <= Self::quota(who))
+				.ok_or_else(|| <Error<T>>::QuotaExceeded.into())
+		}
+
+		/// Fee for uploading `file_size` bytes with the given content id, discounted by
+		/// `DuplicateUploadFee` when the contents are already stored.
+		pub fn upload_fee(content_id: &H256, file_size: u64) -> BalanceOf<T> {
+			let fee = T::UploadFeePerByte::get().saturating_mul(file_size.saturated_into());
+			if <Contents<T>>::contains_key(content_id) {
+				T::DuplicateUploadFee::get() * fee
+			} else {
+				fee
+			}
+		}
+
+		/// Bytes taken up by every version of the file.
+		pub fn stored_bytes(file_id: &T::Hash, file: &File<T>) -> u64 {
+			<FileVersions<T>>::get(file_id)
+				.iter()
+				.fold(file.file_size, |bytes, previous| bytes.saturating_add(previous.file_size))
+		}
+
+		/// Rent owed for storing `bytes` bytes for `blocks` blocks.
+		pub fn rent_cost(bytes: u64, blocks: T::BlockNumber) -> BalanceOf<T> {
+			let bytes: BalanceOf<T> = bytes.saturated_into();
+			let blocks: BalanceOf<T> = blocks.saturated_into::<u64>().saturated_into();
+			T::RentPerByte::get().saturating_mul(bytes).saturating_mul(blocks)
+		}
+
+		/// Whether the rent of `file_id` has run out.
+		pub fn rent_overdue(file_id: &T::Hash) -> bool {
+			Self::file_rent(file_id)
+				.map_or(false, |rent| rent.paid_through < <frame_system::Pallet<T>>::block_number())
+		}
+
+		/// Walks the files paying rent from where the previous call stopped, for as long as
+		/// `remaining_weight` allows. Files whose rent ran out are marked expired, and files past
+		/// the grace period are removed. Returns the weight used.
+		fn collect_expired(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let mut files = match <RentCursor<T>>::get() {
+				Some(last) => <FileRent<T>>::iter_from(<FileRent<T>>::hashed_key_for(last)),
+				None => <FileRent<T>>::iter(),
+			};
+
+			let mut checked = 0;
+			let mut cursor = None;
+			let mut walked = false;
+			while T::WeightInfo::collect_expired(checked + 1) <= remaining_weight {
+				let (file_id, mut rent) = match files.next() {
+					Some(entry) => entry,
+					// The whole map was walked, so the next call starts over.
+					None => {
+						cursor = None;
+						walked = true;
+						break
+					},
+				};
+				checked += 1;
+				cursor = Some(file_id);
+
+				if rent.paid_through >= now {
+					continue
+				}
+				let removed_after = rent.paid_through.saturating_add(T::RentGracePeriod::get());
+				if removed_after < now {
+					match Self::get_file_details(&file_id) {
+						Some(file) => Self::remove_file(&file_id, &file),
+						None => <FileRent<T>>::remove(&file_id),
+					}
+				} else if !rent.expired {
+					rent.expired = true;
+					<FileRent<T>>::insert(&file_id, rent);
+					Self::deposit_event(Event::FileExpired(file_id, removed_after));
+				}
+			}
+
+			// Without the weight to look at a file the cursor stays put. A cursor on the last key is
+			// cleared even though no file was looked at, or the walk would never start over.
+			if checked == 0 && !walked {
+				return 0
+			}
+			<RentCursor<T>>::set(cursor);
+			T::WeightInfo::collect_expired(checked)
+		}
+
+		/// Removes a file along with its versions and downloads. Contents go with the last file
+		/// version referring to them.
+		fn remove_file(file_id: &T::Hash, file: &File<T>) {
+			let stored_bytes = Self::stored_bytes(file_id, file);
+			<Files<T>>::remove(file_id);
+			<FilesPerUser<T>>::mutate(&file.owner, |owned| owned.retain(|id| id != file_id));
+			<BytesUsed<T>>::mutate(&file.owner, |used| *used = used.saturating_sub(stored_bytes));
+			<AllFilesCount<T>>::mutate(|count| *count = count.saturating_sub(1));
+			let downloads: u64 = <FileDownloads<T>>::drain_prefix(file_id).map(|(_, downloads)| downloads.len() as u64).sum();
+			<AllDownloadsCount<T>>::mutate(|count| *count = count.saturating_sub(downloads));
+			<FileRent<T>>::remove(file_id);
+			// Bounded by `MaxKeyEnvelopes`.
+			<KeyEnvelopes<T>>::drain_prefix(file_id).for_each(drop);
+			<KeyEnvelopeCount<T>>::remove(file_id);
+			<Licenses<T>>::drain_prefix(file_id).for_each(drop);
+			<Listings<T>>::remove(file_id);
+			for (bidder, amount) in <Offers<T>>::take(file_id) {
+				T::Currency::unreserve(&bidder, amount);
+				Self::deposit_event(Event::OfferWithdrawn(*file_id, bidder));
+			}
+			Self::cancel_auction_of(file_id);
+			Self::remove_transfer_offer(file_id);
+			<CoOwners<T>>::remove(file_id);
+			// Bounded by `MaxProposals`.
+			<Proposals<T>>::drain_prefix(file_id).for_each(drop);
+			// The NFT may already be gone if its holder burned it.
+			let _ = T::Nfts::burn(&T::NftCollection::get(), file_id, None);
+			// Buyers still waiting for the key get their payment back.
+			for (buyer, request) in <KeyRequests<T>>::drain_prefix(file_id) {
+				Self::refund_key_request(file_id, &buyer, request.amount);
+			}
 
-			let downloads = <FileDownloads<T>>::get(&file_id);
+			Self::release_content(&file.content_id());
+			for previous in <FileVersions<T>>::take(file_id) {
+				Self::release_content(&previous.content_id());
+			}
 
-			let download_index = downloads.binary_search(&signer);
+			Self::deposit_event(Event::FileRemoved(*file_id));
+		}
 
-			match download_index {
-				Ok(_) => {
-					 Err(<Error<T>>::AlreadyDownloaded)?
+		/// Adds a reference to the contents of `version`, storing them if they are new, and charges
+		/// `who` the upload fee. Returns the number of references to the contents.
+		fn add_content_ref(who: &T::AccountId, version: &FileVersion, replication_factor: u32) -> Result<u32, DispatchError> {
+			let fee = Self::upload_fee(&version.content_id(), version.file_size);
+			let content = match Self::contents(&version.content_id()) {
+				Some(content) => ContentRecord {
+					replication_factor: content.replication_factor.max(replication_factor),
+					refs: content.refs.saturating_add(1),
+					..content
 				},
-				Err(_) => {
-					
+				None => ContentRecord {
+					content_root: version.content_root,
+					file_size: version.file_size,
+					chunk_size: version.chunk_size,
+					replication_factor,
+					refs: 1,
 				},
+			};
+
+			if !fee.is_zero() {
+				T::Currency::transfer(who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+				<StorageFunds<T>>::mutate(&version.content_id(), |fund| *fund = fund.saturating_add(fee));
 			}
 
-			match file.file_type {
-				FileType::Normal => {
-					if file.file_size > 250 {
-						let size_difference = file.file_size.checked_sub(T::FileSizeLimit::get()).ok_or_else(|| <Error<T>>::InvalidOperation)?;
-						let extra_cost = size_difference.checked_mul(T::CostPerByte::get()).ok_or_else(|| <Error<T>>::InvalidOperation)?;
-						let total_cost = extra_cost.checked_add(file.cost).ok_or_else(|| <Error<T>>::InvalidOperation)?;
-						let total_cost_in_balance = total_cost.saturated_into::<BalanceOf<T>>(); 
+			let refs = content.refs;
+			<Contents<T>>::insert(&version.content_id(), content);
+			Ok(refs)
+		}
+
+		/// Drops a reference to the contents, removing them along with their last reference.
+		fn release_content(content_id: &H256) {
+			match Self::contents(content_id) {
+				Some(content) if content.refs > 1 => {
+					<Contents<T>>::insert(content_id, ContentRecord { refs: content.refs - 1, ..content });
+				},
+				Some(content) => Self::remove_content(content_id, &content),
+				None => {},
+			}
+		}
 
-						// Transfer the amount to Dave
-						ensure!(T::Currency::free_balance(&signer) >= total_cost_in_balance, <Error<T>>::NotEnoughBalance);
-						let accountant = Self::accountant().ok_or_else(|| <Error<T>>::AccountantNotSet)?;
-						T::Currency::transfer(&signer, &accountant, total_cost_in_balance, ExistenceRequirement::KeepAlive)?;
-						Self::deposit_event(Event::Downloaded(signer.clone(), file_id, total_cost_in_balance));
+		/// Removes contents along with their provider assignments and storage fund. The fund was
+		/// paid into by every uploader and funder of the contents, so what is left of it goes to the
+		/// accountant rather than to whoever happened to hold the last reference.
+		fn remove_content(content_id: &H256, content: &ContentRecord) {
+			<Contents<T>>::remove(content_id);
 
+			for provider in <ContentProviders<T>>::take(content_id) {
+				<ProviderContents<T>>::mutate(&provider, |hosted| hosted.retain(|root| root != content_id));
+				<Providers<T>>::mutate(&provider, |details| {
+					if let Some(details) = details {
+						details.used = details.used.saturating_sub(content.file_size);
 					}
-					else {
-						let cost_in_balance = file.cost.saturated_into::<BalanceOf<T>>();
-						
-						// Transfer the amount to Dave
-						ensure!(T::Currency::free_balance(&signer) >= cost_in_balance, <Error<T>>::NotEnoughBalance);
-						let accountant = Self::accountant().ok_or_else(|| <Error<T>>::AccountantNotSet)?;
-						T::Currency::transfer(&signer, &accountant, cost_in_balance, ExistenceRequirement::KeepAlive)?;
-						Self::deposit_event(Event::Downloaded(signer.clone(), file_id, cost_in_balance));
-					}
+				});
+				// A challenge on removed contents can no longer be answered.
+				Self::clear_challenge(&provider, content_id);
+			}
+
+			let remainder = <ReplicationOrders<T>>::take(content_id)
+				.iter()
+				.fold(<StorageFunds<T>>::take(content_id), |fund, order| fund.saturating_add(order.reward));
+			// Without an accountant the remainder stays in the pallet account.
+			if let Some(accountant) = Self::accountant().filter(|_| !remainder.is_zero()) {
+				let _ = T::Currency::transfer(&Self::account_id(), &accountant, remainder, ExistenceRequirement::AllowDeath);
+			}
+
+			Self::deposit_event(Event::ContentRemoved(*content_id));
+		}
+
+		/// Account holding the storage funds of all files.
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		fn deregister_provider_weight(files: u32) -> Weight {
+			T::WeightInfo::deregister_provider(files)
+				.saturating_add(T::WeightInfo::replicate().saturating_mul(files as Weight))
+		}
+
+		/// Drops the expired replication orders of the contents, refunding their rewards to their
+		/// storage fund, and opens orders for the copies the contents are missing. Providers are
+		/// picked from a random point of the provider map among the first
+		/// `MaxReplicationCandidates`, skipping `exclude` and providers whose order just expired.
+		/// Returns the number of orders opened.
+		fn replicate_content(content_id: &H256, exclude: Option<&T::AccountId>) -> u32 {
+			let content = match Self::contents(content_id) {
+				Some(content) => content,
+				None => return 0,
+			};
+			let now = <frame_system::Pallet<T>>::block_number();
+			let holders = <ContentProviders<T>>::get(content_id);
+			let mut orders = <ReplicationOrders<T>>::get(content_id);
+			let mut fund = <StorageFunds<T>>::get(content_id);
+
+			let mut lapsed = Vec::new();
+			orders.retain(|order| {
+				if order.expires_at >= now {
+					return true
+				}
+				fund = fund.saturating_add(order.reward);
+				lapsed.push(order.provider.clone());
+				false
+			});
+			for provider in lapsed.iter() {
+				Self::deposit_event(Event::ReplicationOrderExpired(*content_id, provider.clone()));
+			}
+
+			let missing = (content.replication_factor as usize).saturating_sub(holders.len() + orders.len());
+			let expires_at = now.saturating_add(T::ReplicationPeriod::get());
+			let (seed, _) = T::Randomness::random(&b"mydropbox/replication"[..]);
+			let providers = match T::AccountId::decode(&mut TrailingZeroInput::new(seed.as_ref())) {
+				Ok(start) => <Providers<T>>::iter_from(<Providers<T>>::hashed_key_for(start)),
+				Err(_) => <Providers<T>>::iter(),
+			};
+
+			let mut opened = 0;
+			let candidates = providers.chain(<Providers<T>>::iter()).take(T::MaxReplicationCandidates::get() as usize);
+			for (provider, details) in candidates {
+				if opened as usize >= missing {
+					break
+				}
+
+				let reward: BalanceOf<T> = details.price_per_byte.saturating_mul(content.file_size).saturated_into();
+				let eligible = details.status == ProviderStatus::Active &&
+					details.capacity.saturating_sub(details.used) >= content.file_size &&
+					reward <= fund &&
+					exclude != Some(&provider) &&
+					!lapsed.contains(&provider) &&
+					!holders.contains(&provider) &&
+					!orders.iter().any(|order| order.provider == provider);
+				if !eligible {
+					continue
+				}
+
+				let order = ReplicationOrder::<T> { provider: provider.clone(), reward, expires_at };
+				if orders.try_push(order).is_err() {
+					break
+				}
+				fund = fund.saturating_sub(reward);
+				opened += 1;
+
+				Self::deposit_event(Event::ReplicationOrderOpened(*content_id, provider, reward, expires_at));
+			}
+
+			if orders.is_empty() {
+				<ReplicationOrders<T>>::remove(content_id);
+			} else {
+				<ReplicationOrders<T>>::insert(content_id, orders);
+			}
+			if fund.is_zero() {
+				<StorageFunds<T>>::remove(content_id);
+			} else {
+				<StorageFunds<T>>::insert(content_id, fund);
+			}
+			opened
+		}
+
+		/// Refunds the buyers whose key was not delivered in time and takes back their download.
+		/// Returns the number of requests looked at.
+		pub(crate) fn expire_key_requests(now: T::BlockNumber) -> u32 {
+			let expiring = <KeyRequestDeadlines<T>>::take(now);
+			for (file_id, buyer) in expiring.iter() {
+				// Fulfilled requests are already gone, and a newer request has a later deadline.
+				match Self::key_requests(file_id, buyer) {
+					Some(request) if request.deadline < now => {
+						<KeyRequests<T>>::remove(file_id, buyer);
+						let removed = <FileDownloads<T>>::mutate(file_id, request.version, |downloads| {
+							downloads.binary_search(buyer).map(|index| downloads.remove(index)).is_ok()
+						});
+						if removed {
+							<AllDownloadsCount<T>>::mutate(|count| *count = count.saturating_sub(1));
+						}
+						<Licenses<T>>::remove(file_id, (buyer.clone(), request.version));
+						Self::refund_key_request(file_id, buyer, request.amount);
+					},
+					_ => {},
+				}
+			}
+			expiring.len() as u32
+		}
+
+		/// Holds the payment of `buyer` until the owner delivers the key of the file.
+		fn request_key(file_id: &T::Hash, buyer: &T::AccountId, version: u32, amount: BalanceOf<T>) -> DispatchResult {
+			let deadline = <frame_system::Pallet<T>>::block_number().saturating_add(T::KeyRequestTimeout::get());
+			let expires_at = deadline.saturating_add(One::one());
+
+			let mut expiring = <KeyRequestDeadlines<T>>::get(expires_at);
+			expiring.try_push((*file_id, buyer.clone())).map_err(|_| <Error<T>>::TooManyKeyRequests)?;
+			T::Currency::reserve(buyer, amount)?;
+
+			<KeyRequestDeadlines<T>>::insert(expires_at, expiring);
+			<KeyRequests<T>>::insert(file_id, buyer, KeyRequest::<T> { version, amount, deadline });
+
+			Self::deposit_event(Event::KeyRequested(*file_id, buyer.clone(), deadline));
+			Ok(())
+		}
+
+		fn refund_key_request(file_id: &T::Hash, buyer: &T::AccountId, amount: BalanceOf<T>) {
+			T::Currency::unreserve(buyer, amount);
+			Self::deposit_event(Event::KeyRequestRefunded(*file_id, buyer.clone(), amount));
+		}
+
+		/// Drops the transfer offer of a file, if any, along with its entry in the block it expires
+		/// in.
+		fn remove_transfer_offer(file_id: &T::Hash) {
+			if let Some(pending) = <PendingTransfers<T>>::take(file_id) {
+				<TransferOfferDeadlines<T>>::mutate(pending.deadline.saturating_add(One::one()), |expiring| {
+					expiring.retain(|id| id != file_id)
+				});
+			}
+		}
+
+		/// Drops the transfer offers whose deadline passed. Returns the number of deadlines processed.
+		pub(crate) fn expire_transfer_offers(now: T::BlockNumber) -> u32 {
+			let expiring = <TransferOfferDeadlines<T>>::take(now);
+			for file_id in expiring.iter() {
+				// Settled offers are already gone, and a newer offer has a later deadline.
+				match Self::pending_transfers(file_id) {
+					Some(pending) if pending.deadline < now => {
+						<PendingTransfers<T>>::remove(file_id);
+						Self::deposit_event(Event::TransferOfferExpired(*file_id));
+					},
+					_ => {},
+				}
+			}
+			expiring.len() as u32
+		}
+
+		/// Sells the files auctioned until `now` to their highest bids. Auctions without bids, or
+		/// whose file changed hands or cannot be taken by the bidder, are cancelled. Returns the
+		/// number of auctions ended.
+		pub(crate) fn settle_auctions(now: T::BlockNumber) -> u32 {
+			let ending = <AuctionEnds<T>>::take(now);
+			for file_id in ending.iter() {
+				let auction = match <Auctions<T>>::take(file_id) {
+					Some(auction) => auction,
+					None => continue,
+				};
+				match auction.bid {
+					Some((bidder, amount)) => {
+						if Self::settle_auction(file_id, &auction.seller, &bidder, amount).is_err() {
+							T::Currency::unreserve(&bidder, amount);
+							Self::deposit_event(Event::AuctionCancelled(*file_id));
+						}
+					},
+					None => Self::deposit_event(Event::AuctionCancelled(*file_id)),
+				}
+			}
+			ending.len() as u32
+		}
+
+		/// Pays `seller` the bid reserved from `bidder` and hands the file over.
+		#[transactional]
+		fn settle_auction(file_id: &T::Hash, seller: &T::AccountId, bidder: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			let mut file = Self::get_file_details(file_id).ok_or(<Error<T>>::FileNotFound)?;
+			ensure!(file.owner == *seller, <Error<T>>::InvalidSigner);
+
+			Self::pay_owners(file_id, seller, amount, |to, share| {
+				T::Currency::repatriate_reserved(bidder, to, share, BalanceStatus::Free).map(|_| ())
+			})?;
+			Self::sell_file(file_id, &mut file, bidder, amount)
+		}
+
+		/// Closes the auction of a file, if any, and refunds its bid.
+		fn cancel_auction_of(file_id: &T::Hash) {
+			let auction = match <Auctions<T>>::take(file_id) {
+				Some(auction) => auction,
+				None => return,
+			};
+			<AuctionEnds<T>>::mutate(auction.end, |ending| ending.retain(|id| id != file_id));
+			if let Some((bidder, amount)) = auction.bid {
+				T::Currency::unreserve(&bidder, amount);
+			}
+			Self::deposit_event(Event::AuctionCancelled(*file_id));
+		}
+
+		/// Stores the file key for `recipient` and pays the accountant the payment held for it.
+		fn store_envelope(
+			file_id: &T::Hash,
+			recipient: &T::AccountId,
+			envelope: BoundedVec<u8, T::MaxEnvelopeLength>,
+		) -> DispatchResult {
+			if !<KeyEnvelopes<T>>::contains_key(file_id, recipient) {
+				<KeyEnvelopeCount<T>>::try_mutate(file_id, |count| -> DispatchResult {
+					ensure!(*count < T::MaxKeyEnvelopes::get(), <Error<T>>::TooManyEnvelopes);
+					*count += 1;
+					Ok(())
+				})?;
+			}
+			<KeyEnvelopes<T>>::insert(file_id, recipient, envelope);
+			Self::deposit_event(Event::AccessGranted(*file_id, recipient.clone()));
+
+			if let Some(request) = <KeyRequests<T>>::take(file_id, recipient) {
+				let accountant = Self::accountant().ok_or(<Error<T>>::AccountantNotSet)?;
+				T::Currency::repatriate_reserved(recipient, &accountant, request.amount, BalanceStatus::Free)?;
+				Self::deposit_event(Event::KeyRequestFulfilled(*file_id, recipient.clone()));
+			}
+			Ok(())
+		}
+
+		/// Slashes every provider whose challenge deadline passed without an answer. Returns the
+		/// number of deadlines processed.
+		fn expire_challenges(now: T::BlockNumber) -> u32 {
+			let expiring = <ChallengeDeadlines<T>>::take(now);
+			for provider in expiring.iter() {
+				// Answered challenges are already gone, and a newer challenge has a later deadline.
+				match Self::challenges(provider) {
+					Some(challenge) if challenge.deadline < now => {
+						<Challenges<T>>::remove(provider);
+						Self::deposit_event(Event::ChallengeMissed(provider.clone(), challenge.content_id));
+						Self::slash_provider(provider, &challenge.content_id);
+					},
+					_ => {},
+				}
+			}
+			expiring.len() as u32
+		}
+
+		/// Challenges up to `MaxChallengesPerBlock` providers on a random chunk of one of the
+		/// contents they host. Returns the number of challenges issued.
+		fn issue_challenges(now: T::BlockNumber) -> u32 {
+			let (seed, _) = T::Randomness::random(&b"mydropbox/challenge"[..]);
+			let deadline = now.saturating_add(T::ChallengeWindow::get());
+			let expires_at = deadline.saturating_add(One::one());
+			let max_challenges = T::MaxChallengesPerBlock::get() as usize;
+
+			// Start scanning providers at a random point of the map and wrap around to its start.
+			let providers = match T::AccountId::decode(&mut TrailingZeroInput::new(seed.as_ref())) {
+				Ok(start) => <ProviderContents<T>>::iter_from(<ProviderContents<T>>::hashed_key_for(start)),
+				Err(_) => <ProviderContents<T>>::iter(),
+			};
+
+			let mut issued = 0;
+			for (provider, hosted) in providers.chain(<ProviderContents<T>>::iter()).take(max_challenges) {
+				if hosted.is_empty() || <Challenges<T>>::contains_key(&provider) {
+					continue
+				}
+
+				let random = T::Hashing::hash_of(&(seed, &provider));
+				let random = u64::decode(&mut TrailingZeroInput::new(random.as_ref())).unwrap_or_default();
+				let content_id = hosted[(random % hosted.len() as u64) as usize];
+				let chunk_count = match Self::contents(&content_id) {
+					Some(content) if content.chunk_count() > 0 => content.chunk_count(),
+					_ => continue,
+				};
+				let chunk_index = ((random >> 32) % chunk_count as u64) as u32;
+
+				if <ChallengeDeadlines<T>>::try_mutate(expires_at, |providers| providers.try_push(provider.clone())).is_err() {
+					break
+				}
+				<Challenges<T>>::insert(&provider, Challenge::<T> { content_id, chunk_index, deadline });
+				issued += 1;
+
+				Self::deposit_event(Event::ChallengeIssued(provider, content_id, chunk_index, deadline));
+			}
+			issued
+		}
+
+		// Fetches a random sample of file links from the gateway and reports those that could not be
+		// fetched. Only nodes holding an authorised reporter key check.
+		fn check_file_availability(now: T::BlockNumber) -> Result<(), &'static str> {
+			let reporter = match Self::local_reporter() {
+				Some(reporter) => reporter,
+				None => return Ok(()),
+			};
+
+			let sample = Self::sample_files(sp_io::offchain::random_seed());
+			let unavailable = Self::fetch_unavailable(&sample)?;
+			Self::submit_unavailable(now, unavailable, &reporter)
+		}
+
+		/// The first key of the local keystore allowed to sign availability reports.
+		pub fn local_reporter() -> Option<sr25519::Public> {
+			sp_io::crypto::sr25519_public_keys(KEY_TYPE)
+				.into_iter()
+				.find(|key| <AvailabilityReporters<T>>::contains_key(key))
+		}
+
+		// Up to `MaxAvailabilityChecks` distinct files, scanned from a random point of the map.
+		fn sample_files(seed: [u8; 32]) -> Vec<(T::Hash, File<T>)> {
+			let files = match T::Hash::decode(&mut TrailingZeroInput::new(&seed)) {
+				Ok(start) => <Files<T>>::iter_from(<Files<T>>::hashed_key_for(start)),
+				Err(_) => <Files<T>>::iter(),
+			};
+
+			let max_checks = T::MaxAvailabilityChecks::get() as usize;
+			let mut sample: Vec<(T::Hash, File<T>)> = Vec::new();
+			for (file_id, file) in files.chain(<Files<T>>::iter()) {
+				if sample.len() == max_checks || sample.iter().any(|(sampled, _)| *sampled == file_id) {
+					break
+				}
+				sample.push((file_id, file));
+			}
+			sample
+		}
+
+		// Requests every sampled link at once and returns the files whose request failed, timed out
+		// or did not succeed.
+		fn fetch_unavailable(sample: &[(T::Hash, File<T>)]) -> Result<Vec<T::Hash>, &'static str> {
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+
+			let mut requests = Vec::new();
+			for (_, file) in sample {
+				let url = Self::file_url(&file.file_link);
+				let url = sp_std::str::from_utf8(&url).map_err(|_| "Gateway is not valid UTF-8")?;
+				let request = http::Request::get(url)
+					.deadline(deadline)
+					.send()
+					.map_err(|_| "Unable to send request to the gateway")?;
+				requests.push(request);
+			}
+
+			let responses = http::PendingRequest::try_wait_all(requests, deadline);
+			Ok(sample
+				.iter()
+				.zip(responses)
+				.filter(|(_, response)| !matches!(response, Ok(Ok(response)) if Self::is_available(response.code)))
+				.map(|((file_id, _), _)| *file_id)
+				.collect())
+		}
+
+		pub(crate) fn submit_unavailable(
+			now: T::BlockNumber,
+			unavailable: Vec<T::Hash>,
+			reporter: &sr25519::Public,
+		) -> Result<(), &'static str> {
+			if unavailable.is_empty() {
+				return Ok(())
+			}
+
+			let files = BoundedVec::try_from(unavailable).map_err(|_| "Too many unavailable files")?;
+			let payload = Self::availability_report_payload(&now, &files);
+			let signature =
+				sp_io::crypto::sr25519_sign(KEY_TYPE, reporter, &payload).ok_or("Reporter key is not in the keystore")?;
+
+			let call = Call::report_unavailable { block_number: now, files, reporter: *reporter, signature };
+			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+				.map_err(|()| "Unable to submit unsigned transaction")
+		}
+
+		/// URL of `file_link` on the configured gateway.
+		pub fn file_url(file_link: &[u8; 20]) -> Vec<u8> {
+			let mut url = T::AvailabilityGateway::get().as_bytes().to_vec();
+			Self::push_hex(&mut url, file_link);
+			url
+		}
+
+		/// URL of the key service at `service` answering with the key of `file_id` encrypted for
+		/// `buyer`.
+		pub fn key_service_url(service: &[u8], file_id: &T::Hash, buyer: &T::AccountId) -> Vec<u8> {
+			let mut url = service.to_vec();
+			Self::push_hex(&mut url, file_id.as_ref());
+			url.push(b'/');
+			Self::push_hex(&mut url, &buyer.encode());
+			url
+		}
+
+		fn push_hex(url: &mut Vec<u8>, bytes: &[u8]) {
+			const HEX: &[u8; 16] = b"0123456789abcdef";
+			for byte in bytes {
+				url.push(HEX[(byte >> 4) as usize]);
+				url.push(HEX[(byte & 0xf) as usize]);
+			}
+		}
+
+		/// Message a reporter key signs to report `files` unavailable at the check of `block_number`.
+		pub fn availability_report_payload(block_number: &T::BlockNumber, files: &[T::Hash]) -> Vec<u8> {
+			(b"mydropbox/availability", block_number, files).encode()
+		}
+
+		fn is_valid_report(
+			block_number: &T::BlockNumber,
+			files: &[T::Hash],
+			reporter: &sr25519::Public,
+			signature: &sr25519::Signature,
+		) -> bool {
+			let payload = Self::availability_report_payload(block_number, files);
+			<AvailabilityReporters<T>>::contains_key(reporter) && sp_io::crypto::sr25519_verify(signature, &payload, reporter)
+		}
+
+		/// Message the owner's delivery key signs to deliver `envelope` to `buyer`.
+		pub fn key_delivery_payload(file_id: &T::Hash, buyer: &T::AccountId, envelope: &[u8]) -> Vec<u8> {
+			(b"mydropbox/key", file_id, buyer, envelope).encode()
+		}
+
+		fn is_valid_key_delivery(
+			file_id: &T::Hash,
+			buyer: &T::AccountId,
+			envelope: &[u8],
+			signature: &sr25519::Signature,
+		) -> bool {
+			let payload = Self::key_delivery_payload(file_id, buyer, envelope);
+			Self::get_file_details(file_id)
+				.and_then(|file| Self::delivery_key(&file.owner))
+				.map_or(false, |key| sp_io::crypto::sr25519_verify(signature, &payload, &key))
+		}
+
+		// Delivers the keys bought in this block, and once more those bought half a timeout ago, from
+		// owners whose delivery key is in the local keystore. Only the deadline buckets of these
+		// blocks are read, so at most twice `MaxKeyRequestsPerBlock` requests are looked at.
+		fn deliver_keys(now: T::BlockNumber) {
+			let local_keys = sp_io::crypto::sr25519_public_keys(KEY_TYPE);
+			if local_keys.is_empty() {
+				return
+			}
+
+			// Requests are filed under the block after their deadline.
+			let timeout = T::KeyRequestTimeout::get();
+			let bought_now = now.saturating_add(timeout).saturating_add(One::one());
+			let bought_before = bought_now.saturating_sub(timeout / T::BlockNumber::from(2u32));
+			let mut buckets = sp_std::vec![bought_now];
+			if bought_before != bought_now {
+				buckets.push(bought_before);
+			}
+
+			let pending = buckets
+				.into_iter()
+				.flat_map(|expires_at| <KeyRequestDeadlines<T>>::get(expires_at).into_inner())
+				.filter_map(|(file_id, buyer)| {
+					// Fulfilled and refunded requests are gone.
+					let file = Self::key_requests(&file_id, &buyer).and_then(|_| Self::get_file_details(&file_id))?;
+					let key = Self::delivery_key(&file.owner).filter(|key| local_keys.contains(key))?;
+					Some((file_id, buyer, key))
+				})
+				.collect::<Vec<_>>();
+			if pending.is_empty() {
+				return
+			}
+
+			let service = match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, KEY_SERVICE_STORAGE_KEY) {
+				Some(service) => service,
+				None => {
+					log::warn!(target: "runtime::mydropbox", "Key delivery skipped: no key service is set");
+					return
 				},
-				FileType::Privileged => {
-					let cost_in_balance = file.cost.saturated_into::<BalanceOf<T>>();
-					
-					// Transfer the amount to Dave
-					ensure!(T::Currency::free_balance(&signer) >= cost_in_balance, <Error<T>>::NotEnoughBalance);
-					let accountant = Self::accountant().ok_or_else(|| <Error<T>>::AccountantNotSet)?;
-					T::Currency::transfer(&signer, &accountant, cost_in_balance, ExistenceRequirement::KeepAlive)?;
-					Self::deposit_event(Event::Downloaded(signer.clone(), file_id, cost_in_balance));
+			};
+
+			for (file_id, buyer, key) in pending {
+				if let Err(e) = Self::deliver_key(&service, file_id, buyer, &key) {
+					log::warn!(target: "runtime::mydropbox", "Key delivery failed: {}", e);
 				}
 			}
+		}
+
+		pub(crate) fn deliver_key(
+			service: &[u8],
+			file_id: T::Hash,
+			buyer: T::AccountId,
+			key: &sr25519::Public,
+		) -> Result<(), &'static str> {
+			let envelope = Self::fetch_envelope(service, &file_id, &buyer)?;
+			let payload = Self::key_delivery_payload(&file_id, &buyer, &envelope);
+			let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, key, &payload).ok_or("Delivery key is not in the keystore")?;
 
-			let downloads_count = Self::all_downloads_count().checked_add(1).ok_or(<Error<T>>::FileDownloadCountOverflow)?;
+			let call = Call::fulfill_key_request { file_id, buyer, envelope, signature };
+			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+				.map_err(|()| "Unable to submit unsigned transaction")
+		}
 
-			<FileDownloads<T>>::try_mutate(&file_id, |download_vec| download_vec.try_push(signer))
-			.map_err(|_| <Error<T>>::ExceedMaxFileDownload)?;
+		// Asks the key service for the file key encrypted for `buyer`.
+		fn fetch_envelope(
+			service: &[u8],
+			file_id: &T::Hash,
+			buyer: &T::AccountId,
+		) -> Result<BoundedVec<u8, T::MaxEnvelopeLength>, &'static str> {
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
 
-			<AllDownloadsCount<T>>::put(downloads_count);
+			let url = Self::key_service_url(service, file_id, buyer);
+			let url = sp_std::str::from_utf8(&url).map_err(|_| "Key service is not valid UTF-8")?;
+			let response = http::Request::get(url)
+				.deadline(deadline)
+				.send()
+				.map_err(|_| "Unable to send request to the key service")?
+				.try_wait(deadline)
+				.map_err(|_| "Key service timed out")?
+				.map_err(|_| "Key service request failed")?;
+			if response.code != 200 {
+				return Err("Key service has no key for the buyer")
+			}
+
+			BoundedVec::try_from(response.body().collect::<Vec<u8>>()).map_err(|_| "Key envelope is too long")
+		}
+
+		/// Whether a gateway response with HTTP status `code` means the file can be fetched.
+		pub fn is_available(code: u16) -> bool {
+			(200..300).contains(&code)
+		}
+
+		/// Checks a chunk inclusion proof against the content root of `file_id`. Unknown files
+		/// never verify.
+		pub fn verify_file_chunk(file_id: &T::Hash, index: u32, chunk: &[u8], proof: &[H256]) -> bool {
+			Self::get_file_details(file_id).map_or(false, |file| file.verify_chunk(index, chunk, proof))
+		}
+
+		/// Assigns `provider` to the contents if it is active and has room for them.
+		fn attach_provider(content_id: &H256, provider: &T::AccountId) -> DispatchResult {
+			let (used, mut assigned, mut hosted) = Self::ensure_can_host(content_id, provider)?;
+			assigned.try_push(provider.clone()).map_err(|_| <Error<T>>::ExceedMaxProvidersPerFile)?;
+			hosted.try_push(*content_id).map_err(|_| <Error<T>>::ExceedMaxFilesPerProvider)?;
 
+			<Providers<T>>::mutate(provider, |details| {
+				if let Some(details) = details {
+					details.used = used;
+				}
+			});
+			<ContentProviders<T>>::insert(content_id, assigned);
+			<ProviderContents<T>>::insert(provider, hosted);
 			Ok(())
 		}
 
-		// Transfer
-		#[pallet::weight(100)]
-		pub fn transfer_file(origin: OriginFor<T>, file_id: T::Hash, new_owner: T::AccountId) -> DispatchResult {
-			let owner = ensure_signed(origin)?;
-			
-			let file = &mut <Files<T>>::get(&file_id).ok_or(<Error<T>>::FileNotFound)?;
+		/// Checks that `provider` is active, has room for the contents and is not assigned to them
+		/// yet. Returns the space the provider would use, and the assignments of both sides.
+		fn ensure_can_host(
+			content_id: &H256,
+			provider: &T::AccountId,
+		) -> Result<
+			(u64, BoundedVec<T::AccountId, T::MaxProvidersPerFile>, BoundedVec<H256, T::MaxFilesPerProvider>),
+			DispatchError,
+		> {
+			let content = Self::contents(content_id).ok_or(<Error<T>>::FileNotFound)?;
+			let details = Self::providers(provider).ok_or(<Error<T>>::ProviderNotFound)?;
+			ensure!(details.status == ProviderStatus::Active, <Error<T>>::ProviderNotActive);
+			let used = details.used.checked_add(content.file_size).ok_or(<Error<T>>::InvalidOperation)?;
+			ensure!(used <= details.capacity, <Error<T>>::NotEnoughCapacity);
 
-			ensure!(file.owner == owner, <Error<T>>::InvalidSigner);
+			let assigned = <ContentProviders<T>>::get(content_id);
+			ensure!(!assigned.contains(provider), <Error<T>>::ProviderAlreadyAssigned);
+			ensure!(assigned.len() < T::MaxProvidersPerFile::get() as usize, <Error<T>>::ExceedMaxProvidersPerFile);
+			let hosted = <ProviderContents<T>>::get(provider);
+			ensure!(hosted.len() < T::MaxFilesPerProvider::get() as usize, <Error<T>>::ExceedMaxFilesPerProvider);
+			Ok((used, assigned, hosted))
+		}
 
-			file.owner = new_owner.clone();
+		/// Ends the assignment of `provider` to the contents, freeing the space they took up.
+		/// Returns false if the provider was not assigned to them.
+		fn remove_assignment(content_id: &H256, provider: &T::AccountId) -> bool {
+			if !Self::detach_provider(content_id, provider) {
+				return false
+			}
+			let file_size = Self::contents(content_id).map_or(0, |content| content.file_size);
+			<ProviderContents<T>>::mutate(provider, |hosted| hosted.retain(|root| root != content_id));
+			<Providers<T>>::mutate(provider, |details| {
+				if let Some(details) = details {
+					details.used = details.used.saturating_sub(file_size);
+				}
+			});
+			true
+		}
+
+		/// Removes `provider` from the providers of the contents, returning whether it was assigned.
+		/// An open challenge on the contents goes too, as the provider no longer has to store them.
+		fn detach_provider(content_id: &H256, provider: &T::AccountId) -> bool {
+			let mut assigned = <ContentProviders<T>>::get(content_id);
+			let index = match assigned.iter().position(|id| id == provider) {
+				Some(index) => index,
+				None => return false,
+			};
+
+			assigned.swap_remove(index);
+			if assigned.is_empty() {
+				<ContentProviders<T>>::remove(content_id);
+			} else {
+				<ContentProviders<T>>::insert(content_id, assigned);
+			}
+			Self::clear_challenge(provider, content_id);
+			true
+		}
+
+		/// Drops the open challenge of `provider` if it is on the contents.
+		fn clear_challenge(provider: &T::AccountId, content_id: &H256) {
+			if Self::challenges(provider).map_or(false, |challenge| challenge.content_id == *content_id) {
+				<Challenges<T>>::remove(provider);
+			}
+		}
 
-			<FilesPerUser<T>>::try_mutate(&owner, |owned| {
-				if let Some(ind) = owned.iter().position(|&id| id == file_id) {
-					owned.swap_remove(ind);
-					return Ok(())
+		/// Price of downloading `file` for the use of `tier`: its cost or commercial cost, plus
+		/// `CostPerByte` for every byte above `FileSizeLimit` when a normal file is larger than 250
+		/// bytes.
+		fn download_cost(file: &File<T>, tier: LicenseTier) -> Result<BalanceOf<T>, Error<T>> {
+			let cost = match tier {
+				LicenseTier::Personal => file.cost,
+				LicenseTier::Commercial => file.license.commercial_cost.ok_or(<Error<T>>::CommercialUseNotOffered)?,
+			};
+			let total_cost = match file.file_type {
+				FileType::Normal | FileType::Encrypted if file.file_size > 250 => {
+					let size_difference = file.file_size.checked_sub(T::FileSizeLimit::get()).ok_or(<Error<T>>::InvalidOperation)?;
+					let extra_cost = size_difference.checked_mul(T::CostPerByte::get()).ok_or(<Error<T>>::InvalidOperation)?;
+					extra_cost.checked_add(cost).ok_or(<Error<T>>::InvalidOperation)?
+				},
+				FileType::Normal | FileType::Privileged | FileType::Encrypted => cost,
+			};
+
+			Ok(total_cost.saturated_into::<BalanceOf<T>>())
+		}
+
+		/// Returns a page of files matching `filter`, starting after the file id `cursor`, and the
+		/// cursor of the next page.
+		///
+		/// At most `limit` entries are returned, capped at `MaxPageSize`, and at most `MaxPageScan`
+		/// files are examined, so a page may come back short or empty while more files match. The
+		/// next cursor is the id of the last file examined, and `None` once every file was.
+		pub fn list_files(
+			filter: FileFilter<T::AccountId>,
+			cursor: Option<T::Hash>,
+			limit: u32,
+		) -> (Vec<(T::Hash, File<T>)>, Option<T::Hash>) {
+			match filter {
+				FileFilter::Owner(ref owner) => {
+					// Transfers reorder the owner list, so pages follow the ids in sorted order
+					// instead. A file leaving or joining between pages then only affects itself.
+					let mut owned = <FilesPerUser<T>>::get(owner).into_inner();
+					owned.sort();
+					let start = match cursor {
+						Some(cursor) => owned.partition_point(|id| *id <= cursor),
+						None => 0,
+					};
+
+					let files = owned
+						.into_iter()
+						.skip(start)
+						.filter_map(|id| Self::get_file_details(&id).map(|file| (id, file)));
+					Self::page_files(&filter, files, cursor, limit)
+				},
+				_ => {
+					let files = match cursor {
+						Some(cursor) => <Files<T>>::iter_from(<Files<T>>::hashed_key_for(cursor)),
+						None => <Files<T>>::iter(),
+					};
+
+					Self::page_files(&filter, files, cursor, limit)
+				},
+			}
+		}
+
+		/// Collects up to `limit` files of `files` matching `filter`, examining at most
+		/// `MaxPageScan` of them, along with the id of the last one examined unless `files` ran out.
+		/// `files` follow `cursor`, which is returned again when none of them is examined.
+		fn page_files(
+			filter: &FileFilter<T::AccountId>,
+			files: impl Iterator<Item = (T::Hash, File<T>)>,
+			cursor: Option<T::Hash>,
+			limit: u32,
+		) -> (Vec<(T::Hash, File<T>)>, Option<T::Hash>) {
+			let limit = limit.min(T::MaxPageSize::get()) as usize;
+			let scan = T::MaxPageScan::get() as usize;
+			let mut page = Vec::new();
+			let mut last = cursor;
+			let mut examined = 0;
+
+			for (file_id, file) in files {
+				if page.len() >= limit || examined >= scan {
+					return (page, last)
+				}
+				examined += 1;
+				last = Some(file_id);
+				if Self::file_matches(filter, &file) {
+					page.push((file_id, file));
+				}
+			}
+
+			(page, None)
+		}
+
+		/// Checks the bookkeeping invariants of the pallet's storage.
+		///
+		/// - `AllFilesCount` equals the number of entries in `Files`.
+		/// - Every file id is listed in exactly one `FilesPerUser` entry, that of its owner.
+		/// - Every downloader list is strictly sorted, so nobody is recorded (and charged) twice,
+		///   and `AllDownloadsCount` equals the total number of recorded downloads.
+		/// - Every content record is referred to by as many files as it counts, all with its size.
+		/// - `ContentProviders` and `ProviderContents` mirror each other, only registered providers
+		///   host contents, and only stored contents are hosted or funded.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let mut files_count = 0u64;
+			let mut refs = BTreeMap::new();
+			for (file_id, file) in <Files<T>>::iter() {
+				files_count += 1;
+				ensure!(
+					<FilesPerUser<T>>::get(&file.owner).contains(&file_id),
+					"file is missing from its owner's list"
+				);
+				ensure!(
+					T::Nfts::owner(&T::NftCollection::get(), &file_id).map_or(true, |holder| holder == file.owner),
+					"file NFT is held by someone else"
+				);
+				let history = <FileVersions<T>>::get(&file_id);
+				for version in history.iter().cloned().chain(sp_std::iter::once(file.current_version())) {
+					let content = Self::contents(&version.content_id()).ok_or("file contents are not stored")?;
+					ensure!(
+						content.content_root == version.content_root &&
+							content.file_size == version.file_size &&
+							content.chunk_size == version.chunk_size,
+						"file version does not match its contents"
+					);
+					*refs.entry(version.content_id()).or_insert(0u32) += 1;
 				}
-				Err(())
-			})
-			.map_err(|_| <Error<T>>::FileDoesntExist)?;
+			}
+			for file_id in <FileVersions<T>>::iter_keys() {
+				ensure!(<Files<T>>::contains_key(&file_id), "versions are kept for a missing file");
+			}
+			ensure!(files_count == Self::all_files_count(), "AllFilesCount does not match Files");
+			for (content_id, content) in <Contents<T>>::iter() {
+				ensure!(
+					refs.get(&content_id) == Some(&content.refs),
+					"content refs do not match the files referring to it"
+				);
+			}
 
-			<Files<T>>::insert(file_id, file);
-			<FilesPerUser<T>>::try_mutate(&new_owner, |file_vec| file_vec.try_push(file_id))
-			.map_err(|_| <Error<T>>::ExceedMaxFileUploaded)?;	
+			let mut listed = BTreeSet::new();
+			for (owner, owned) in <FilesPerUser<T>>::iter() {
+				let mut owned_bytes = 0u64;
+				for file_id in owned {
+					ensure!(listed.insert(file_id), "file is listed more than once");
+					let file = Self::get_file_details(&file_id).ok_or("listed file does not exist")?;
+					ensure!(file.owner == owner, "file is listed under an account that does not own it");
+					owned_bytes = owned_bytes.saturating_add(Self::stored_bytes(&file_id, &file));
+				}
+				ensure!(owned_bytes == Self::bytes_used(&owner), "BytesUsed does not match the files owned");
+			}
+			for (owner, used) in <BytesUsed<T>>::iter() {
+				ensure!(
+					used == 0 || <FilesPerUser<T>>::contains_key(&owner),
+					"BytesUsed is tracked for an account without files"
+				);
+			}
+
+			let mut downloads_count = 0u64;
+			for (_, _, downloaders) in <FileDownloads<T>>::iter() {
+				ensure!(
+					downloaders.windows(2).all(|pair| pair[0] < pair[1]),
+					"downloader list is not strictly sorted"
+				);
+				downloads_count += downloaders.len() as u64;
+			}
+			ensure!(
+				downloads_count == Self::all_downloads_count(),
+				"AllDownloadsCount does not match FileDownloads"
+			);
 
+			for (provider, hosted) in <ProviderContents<T>>::iter() {
+				ensure!(<Providers<T>>::contains_key(&provider), "unregistered provider hosts contents");
+				for content_id in hosted {
+					ensure!(
+						<ContentProviders<T>>::get(&content_id).contains(&provider),
+						"provider hosts contents it is not assigned to"
+					);
+				}
+			}
+			for (content_id, assigned) in <ContentProviders<T>>::iter() {
+				ensure!(<Contents<T>>::contains_key(&content_id), "missing contents are hosted");
+				for provider in assigned {
+					ensure!(
+						<ProviderContents<T>>::get(&provider).contains(&content_id),
+						"contents are assigned to a provider that does not host them"
+					);
+				}
+			}
+			for content_id in <StorageFunds<T>>::iter_keys() {
+				ensure!(<Contents<T>>::contains_key(&content_id), "missing contents are funded");
+			}
+			for file_id in <FileRent<T>>::iter_keys() {
+				ensure!(<Files<T>>::contains_key(&file_id), "rent is tracked for a missing file");
+			}
+			for (file_id, listing) in <Listings<T>>::iter() {
+				let file = Self::get_file_details(&file_id).ok_or("missing file is listed")?;
+				ensure!(listing.seller == file.owner, "file is listed by an account that does not own it");
+			}
+			for file_id in <Offers<T>>::iter_keys() {
+				ensure!(<Files<T>>::contains_key(&file_id), "offers are made on a missing file");
+			}
+			for (file_id, co_ownership) in <CoOwners<T>>::iter() {
+				let file = Self::get_file_details(&file_id).ok_or("missing file is co-owned")?;
+				ensure!(file.owner == Self::co_owner_account(&file_id), "co-owned file is not owned by the co-owners");
+				ensure!(
+					co_ownership.threshold > 0 && co_ownership.threshold as usize <= co_ownership.owners.len(),
+					"co-owners need more approvals than they can give"
+				);
+			}
+			for (file_id, proposal_id, _) in <Proposals<T>>::iter() {
+				let co_ownership = Self::co_owners(&file_id).ok_or("proposal on a file without co-owners")?;
+				ensure!(proposal_id < co_ownership.next_proposal_id, "proposal id was never handed out");
+			}
+			for (file_id, co_ownership) in <CoOwners<T>>::iter() {
+				ensure!(
+					<Proposals<T>>::iter_prefix(&file_id).count() as u32 == co_ownership.open_proposals,
+					"open proposals are miscounted"
+				);
+			}
+			for (file_id, count) in <KeyEnvelopeCount<T>>::iter() {
+				ensure!(<Files<T>>::contains_key(&file_id), "envelopes are counted for a missing file");
+				ensure!(
+					count > 0 && <KeyEnvelopes<T>>::iter_prefix(&file_id).count() as u32 == count,
+					"key envelopes are miscounted"
+				);
+			}
+			for file_id in <KeyEnvelopes<T>>::iter_keys().map(|(file_id, _)| file_id) {
+				ensure!(<KeyEnvelopeCount<T>>::get(&file_id) > 0, "key envelopes are not counted");
+			}
+			for (file_id, pending) in <PendingTransfers<T>>::iter() {
+				let file = Self::get_file_details(&file_id).ok_or("missing file is offered")?;
+				ensure!(pending.from == file.owner, "file is offered by an account that does not own it");
+				ensure!(
+					<TransferOfferDeadlines<T>>::get(pending.deadline.saturating_add(One::one())).contains(&file_id),
+					"transfer offer never expires"
+				);
+			}
+			for (_, expiring) in <TransferOfferDeadlines<T>>::iter() {
+				for file_id in expiring.iter() {
+					ensure!(<PendingTransfers<T>>::contains_key(file_id), "deadline is kept for a dropped transfer offer");
+				}
+			}
+			for (file_id, auction) in <Auctions<T>>::iter() {
+				let file = Self::get_file_details(&file_id).ok_or("missing file is auctioned")?;
+				ensure!(auction.seller == file.owner, "file is auctioned by an account that does not own it");
+				ensure!(!<Listings<T>>::contains_key(&file_id), "auctioned file is listed");
+				ensure!(<AuctionEnds<T>>::get(auction.end).contains(&file_id), "auction is never settled");
+			}
+			for (file_id, (holder, version), license) in <Licenses<T>>::iter() {
+				ensure!(license.version == version, "license is kept under another version");
+				ensure!(
+					<FileDownloads<T>>::get(&file_id, version).binary_search(&holder).is_ok(),
+					"license is held by an account that did not download the file"
+				);
+			}
+			for (file_id, buyer, request) in <KeyRequests<T>>::iter() {
+				ensure!(
+					<FileDownloads<T>>::get(&file_id, request.version).binary_search(&buyer).is_ok(),
+					"key is requested by a buyer who did not download the file"
+				);
+			}
+			for (content_id, orders) in <ReplicationOrders<T>>::iter() {
+				let assigned = <ContentProviders<T>>::get(&content_id);
+				for order in orders {
+					ensure!(
+						!assigned.contains(&order.provider),
+						"replication order for a provider that already holds the contents"
+					);
+				}
+			}
 
-			Self::deposit_event(Event::Transfered(owner,new_owner, file_id));
 			Ok(())
 		}
+
+		fn file_matches(filter: &FileFilter<T::AccountId>, file: &File<T>) -> bool {
+			match filter {
+				FileFilter::All => true,
+				FileFilter::Owner(owner) => file.owner == *owner,
+				FileFilter::Type(file_type) => file.file_type == *file_type,
+				FileFilter::PriceRange(min, max) => file.cost >= *min && file.cost <= *max,
+			}
+		}
     }
 
+	/// Keeps the file NFTs from being transferred through the NFT pallet, which would move them
+	/// without the file. The pallet hands a NFT over right after storing the file with its new
+	/// owner, which is the only time the two differ. Burning is not locked, so a file may lose its
+	/// NFT until it changes hands.
+	impl<T: Config> Locker<CollectionIdOf<T>, T::Hash> for Pallet<T>
+	where
+		CollectionIdOf<T>: PartialEq,
+	{
+		fn is_locked(collection: CollectionIdOf<T>, item: T::Hash) -> bool {
+			collection == T::NftCollection::get() &&
+				Self::get_file_details(&item)
+					.map_or(true, |file| T::Nfts::owner(&collection, &item) == Some(file.owner))
+		}
+	}
 
-    impl<T: Config> Pallet<T> {
-        
-    }
+	/// `CreateOrigin` of the NFT pallet letting `O` create every collection but `NftCollection`,
+	/// which only this pallet creates.
+	pub struct EnsureNotNftCollection<T, O>(PhantomData<(T, O)>);
+
+	impl<T: Config, O: EnsureOriginWithArg<T::Origin, CollectionIdOf<T>>> EnsureOriginWithArg<T::Origin, CollectionIdOf<T>>
+		for EnsureNotNftCollection<T, O>
+	where
+		CollectionIdOf<T>: PartialEq,
+	{
+		type Success = O::Success;
+
+		fn try_origin(o: T::Origin, collection: &CollectionIdOf<T>) -> Result<Self::Success, T::Origin> {
+			if *collection == T::NftCollection::get() {
+				return Err(o)
+			}
+			O::try_origin(o, collection)
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn successful_origin(collection: &CollectionIdOf<T>) -> T::Origin {
+			O::successful_origin(collection)
+		}
+	}
 }
\ No newline at end of file
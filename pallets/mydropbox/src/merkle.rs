@@ -0,0 +1,96 @@
+//! Binary Merkle trees over the chunks of a file.
+//!
+//! Leaves are the blake2-256 hashes of the chunks, and each parent is the hash of its two children
+//! concatenated. A node without a sibling is carried up to the next level unchanged.
+
+use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+/// Number of `chunk_size`-byte chunks `file_size` bytes are split into.
+pub fn chunk_count(file_size: u64, chunk_size: u32) -> u32 {
+	let chunk_size = chunk_size.max(1) as u64;
+	let chunk_count = file_size / chunk_size + (file_size % chunk_size != 0) as u64;
+	chunk_count.try_into().unwrap_or(u32::MAX)
+}
+
+/// Key the contents with the given root are stored under. The size and chunk size the root was
+/// built with are part of it, so contents registered with the wrong sizes never collide with the
+/// real ones.
+pub fn content_id(root: &H256, file_size: u64, chunk_size: u32) -> H256 {
+	let mut preimage = [0u8; 44];
+	preimage[..32].copy_from_slice(root.as_bytes());
+	preimage[32..40].copy_from_slice(&file_size.to_le_bytes());
+	preimage[40..].copy_from_slice(&chunk_size.to_le_bytes());
+	H256(blake2_256(&preimage))
+}
+
+/// Hash of a single chunk, used as a leaf of the tree.
+pub fn hash_leaf(chunk: &[u8]) -> H256 {
+	H256(blake2_256(chunk))
+}
+
+fn hash_node(left: &H256, right: &H256) -> H256 {
+	let mut pair = [0u8; 64];
+	pair[..32].copy_from_slice(left.as_bytes());
+	pair[32..].copy_from_slice(right.as_bytes());
+	H256(blake2_256(&pair))
+}
+
+fn next_level(level: &[H256]) -> Vec<H256> {
+	level
+		.chunks(2)
+		.map(|pair| match pair {
+			[left, right] => hash_node(left, right),
+			[single] => *single,
+			_ => unreachable!("chunks(2) yields one or two nodes"),
+		})
+		.collect()
+}
+
+/// Root of the tree over `leaves`, or the zero hash when there are none.
+pub fn root(leaves: &[H256]) -> H256 {
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		level = next_level(&level);
+	}
+	level.first().copied().unwrap_or_default()
+}
+
+/// Sibling hashes needed to prove the leaf at `index`, ordered from the leaf up to the root.
+pub fn proof(leaves: &[H256], mut index: usize) -> Vec<H256> {
+	let mut proof = Vec::new();
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		if let Some(sibling) = level.get(index ^ 1) {
+			proof.push(*sibling);
+		}
+		level = next_level(&level);
+		index /= 2;
+	}
+	proof
+}
+
+/// Checks that `leaf` is the leaf at `index` of a tree with `leaf_count` leaves and the given root.
+pub fn verify(root: &H256, leaf: H256, mut index: u32, leaf_count: u32, proof: &[H256]) -> bool {
+	if index >= leaf_count {
+		return false
+	}
+
+	let mut hash = leaf;
+	let mut width = leaf_count;
+	let mut siblings = proof.iter();
+	while width > 1 {
+		if index ^ 1 < width {
+			let sibling = match siblings.next() {
+				Some(sibling) => sibling,
+				None => return false,
+			};
+			hash = if index % 2 == 0 { hash_node(&hash, sibling) } else { hash_node(sibling, &hash) };
+		}
+		index /= 2;
+		width = (width + 1) / 2;
+	}
+
+	siblings.next().is_none() && hash == *root
+}
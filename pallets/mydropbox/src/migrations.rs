@@ -0,0 +1,195 @@
+//! Storage migrations of the pallet.
+
+/// Moves storage written by the first release of the pallet, before storage versions were tracked,
+/// to the layout of version 1.
+pub mod v1 {
+	use crate::{
+		AllDownloadsCount, BalanceOf, BytesUsed, Config, ContentRecord, Contents, File, FileDownloads, FileRent,
+		FileType, Files, License, LicenseTerms, LicenseTier, Licenses, Pallet, RentStatus,
+	};
+	use codec::{Decode, Encode};
+	use frame_support::{
+		sp_runtime::{
+			traits::{Saturating, Zero},
+			DispatchError, TransactionOutcome,
+		},
+		storage::{migration::storage_key_iter, with_transaction},
+		traits::{
+			tokens::nonfungibles::Inspect, Currency, Get, GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess,
+			StorageVersion,
+		},
+		weights::Weight,
+		BoundedVec, Twox64Concat,
+	};
+	use sp_core::H256;
+	use sp_std::{marker::PhantomData, vec::Vec};
+
+	/// A file as the first release stored it.
+	#[derive(Encode, Decode)]
+	pub struct OldFile<AccountId> {
+		pub file_link: [u8; 20],
+		pub allow_download: bool,
+		pub file_type: FileType,
+		pub cost: u64,
+		pub file_size: u64,
+		pub owner: AccountId,
+	}
+
+	/// Merkle root standing in for the unknown root of a file uploaded before contents were
+	/// tracked. It differs for every file, so legacy files never share contents.
+	pub fn legacy_root<Hash: Encode>(file_id: &Hash) -> H256 {
+		H256(sp_io::hashing::blake2_256(&(b"mydropbox/legacy", file_id).encode()))
+	}
+
+	/// Runs `migrate` on runtime upgrade. `CollectionDeposit` and `ItemDeposit` are the deposits the
+	/// NFT pallet takes for a collection and an item.
+	pub struct MigrateToV1<T, CollectionDeposit, ItemDeposit>(PhantomData<(T, CollectionDeposit, ItemDeposit)>);
+
+	impl<T, CollectionDeposit, ItemDeposit> OnRuntimeUpgrade for MigrateToV1<T, CollectionDeposit, ItemDeposit>
+	where
+		T: Config,
+		CollectionDeposit: Get<BalanceOf<T>>,
+		ItemDeposit: Get<BalanceOf<T>>,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			migrate::<T, CollectionDeposit, ItemDeposit>()
+		}
+	}
+
+	/// Rewrites `Files` and `FileDownloads` to their current layout, and fills in what later
+	/// features track for every file: its contents, the owner's used bytes, its NFT, its rent and
+	/// the licenses of its buyers. Does nothing once the storage version is 1.
+	///
+	/// The first release had no NFT account, so the deposits of the collection and of the legacy
+	/// files' NFTs are issued to it. If an NFT still cannot be created, nothing is migrated and the
+	/// storage version stays at 0.
+	///
+	/// Legacy files keep a placeholder content root with no replication asked for, so owners have
+	/// to publish a version with the real root before providers can host them.
+	pub fn migrate<T, CollectionDeposit, ItemDeposit>() -> Weight
+	where
+		T: Config,
+		CollectionDeposit: Get<BalanceOf<T>>,
+		ItemDeposit: Get<BalanceOf<T>>,
+	{
+		if Pallet::<T>::on_chain_storage_version() >= 1 {
+			return 0
+		}
+
+		let mut reads = 1u64;
+		let mut writes = 0u64;
+		let outcome = with_transaction(|| {
+			match migrate_files::<T, CollectionDeposit, ItemDeposit>(&mut reads, &mut writes) {
+				Ok(()) => TransactionOutcome::Commit(Ok(())),
+				Err(e) => TransactionOutcome::Rollback(Err(e)),
+			}
+		});
+		if let Err(e) = outcome {
+			log::error!(target: "runtime::mydropbox", "Storage migration to v1 failed and was rolled back: {:?}", e);
+		}
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	/// Migrates every legacy file, failing if its NFT cannot be created.
+	fn migrate_files<T, CollectionDeposit, ItemDeposit>(reads: &mut u64, writes: &mut u64) -> Result<(), DispatchError>
+	where
+		T: Config,
+		CollectionDeposit: Get<BalanceOf<T>>,
+		ItemDeposit: Get<BalanceOf<T>>,
+	{
+		let now = <frame_system::Pallet<T>>::block_number();
+		let mut migrated = Vec::new();
+		<Files<T>>::translate::<OldFile<T::AccountId>, _>(|file_id, old| {
+			*reads += 1;
+			*writes += 1;
+			migrated.push((file_id, old.owner.clone(), old.file_size));
+			Some(File {
+				file_link: old.file_link,
+				allow_download: old.allow_download,
+				file_type: old.file_type,
+				cost: old.cost,
+				file_size: old.file_size,
+				owner: old.owner,
+				content_root: legacy_root(&file_id),
+				chunk_size: T::MaxChunkSize::get(),
+				replication_factor: 0,
+				version: 0,
+				license: LicenseTerms::default(),
+			})
+		});
+
+		// Chains started before the NFTs were added have no collection yet.
+		let collection = T::NftCollection::get();
+		let create_collection = T::Nfts::collection_owner(&collection).is_none();
+		let nft_account = Pallet::<T>::nft_account();
+		let mut deposits = ItemDeposit::get().saturating_mul((migrated.len() as u32).into());
+		if create_collection {
+			deposits = deposits.saturating_add(CollectionDeposit::get());
+		}
+		let missing = deposits
+			.saturating_add(T::Currency::minimum_balance())
+			.saturating_sub(T::Currency::free_balance(&nft_account));
+		if !missing.is_zero() {
+			drop(T::Currency::deposit_creating(&nft_account, missing));
+		}
+		if create_collection {
+			Pallet::<T>::create_nft_collection()?;
+		}
+		*reads += 2;
+		*writes += 2;
+
+		for (file_id, owner, file_size) in migrated {
+			let content_root = legacy_root(&file_id);
+			let chunk_size = T::MaxChunkSize::get();
+			<Contents<T>>::insert(
+				crate::merkle::content_id(&content_root, file_size, chunk_size),
+				ContentRecord { content_root, file_size, chunk_size, replication_factor: 0, refs: 1 },
+			);
+			<BytesUsed<T>>::mutate(&owner, |used| *used = used.saturating_add(file_size));
+			// Legacy files get the first rent period new uploads pay for, without being charged.
+			if !T::RentPerByte::get().is_zero() {
+				<FileRent<T>>::insert(
+					&file_id,
+					RentStatus { paid_through: now.saturating_add(T::InitialRentPeriod::get()), expired: false },
+				);
+			}
+			Pallet::<T>::mint_nft(&file_id, &owner)?;
+			*reads += 1;
+			*writes += 6;
+		}
+
+		// Downloads were kept per file, and are now kept per version. Every legacy download was of
+		// the only version, and becomes a license for it.
+		let downloads: Vec<(T::Hash, BoundedVec<T::AccountId, T::MaxFilesUploaded>)> =
+			storage_key_iter::<T::Hash, _, Twox64Concat>(Pallet::<T>::name().as_bytes(), b"FileDownloads")
+				.drain()
+				.collect();
+		let mut downloads_count = 0u64;
+		for (file_id, downloaders) in downloads {
+			*reads += 1;
+			*writes += 2;
+			let mut downloaders = downloaders.into_inner();
+			downloaders.sort();
+			downloaders.dedup();
+			// Sorting and deduplicating never makes the list longer, so it still fits.
+			let downloaders: BoundedVec<_, T::MaxFilesUploaded> = downloaders.try_into().unwrap_or_default();
+			downloads_count = downloads_count.saturating_add(downloaders.len() as u64);
+			for buyer in downloaders.iter() {
+				<Licenses<T>>::insert(&file_id, (buyer.clone(), 0), License {
+					version: 0,
+					tier: LicenseTier::Personal,
+					kind: Default::default(),
+					terms_hash: None,
+					fetches: T::FetchesPerLicense::get(),
+				});
+				*writes += 1;
+			}
+			<FileDownloads<T>>::insert(&file_id, 0, downloaders);
+		}
+		<AllDownloadsCount<T>>::put(downloads_count);
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+		*writes += 2;
+		Ok(())
+	}
+}
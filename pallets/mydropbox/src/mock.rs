@@ -1,13 +1,28 @@
 use crate as pallet_mydropbox;
+use frame_support::{
+	parameter_types,
+	traits::{AsEnsureOriginWithArg, ConstU16, ConstU32, ConstU64, GenesisBuild, Randomness},
+	PalletId,
+};
 use frame_system as system;
+use frame_system::{EnsureRoot, EnsureSigned};
 use sp_core::H256;
+use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Hash, IdentityLookup},
+	Perbill,
 };
+use std::sync::Arc;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
+pub type Extrinsic = TestXt<Call, ()>;
+
+/// Account registered as the accountant by tests that need one.
+pub const ACCOUNTANT: u64 = 100;
+/// Account endowed with less than the cost of a typical download.
+pub const POOR: u64 = 50;
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -17,7 +32,9 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		TemplateModule: pallet_mydropbox::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Uniques: pallet_uniques::{Pallet, Call, Storage, Event<T>},
+		TemplateModule: pallet_mydropbox::{Pallet, Call, Config, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -39,7 +56,7 @@ impl system::Config for Test {
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -48,11 +65,135 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+// Deposits are free unless a test sets them, so the pallet's NFT account needs no funds.
+impl pallet_uniques::Config for Test {
+	type Event = Event;
+	type CollectionId = u32;
+	type ItemId = H256;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<u64>;
+	type CreateOrigin = pallet_mydropbox::EnsureNotNftCollection<Test, AsEnsureOriginWithArg<EnsureSigned<u64>>>;
+	// File NFTs only move along with their file.
+	type Locker = TemplateModule;
+	type CollectionDeposit = CollectionDeposit;
+	type ItemDeposit = ItemDeposit;
+	type MetadataDepositBase = ConstU64<0>;
+	type AttributeDepositBase = ConstU64<0>;
+	type DepositPerByte = ConstU64<0>;
+	type StringLimit = ConstU32<50>;
+	type KeyLimit = ConstU32<50>;
+	type ValueLimit = ConstU32<50>;
+	type WeightInfo = ();
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Test
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+/// Deterministic randomness that changes with every block.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		let block_number = System::block_number();
+		(BlakeTwo256::hash_of(&(subject, block_number)), block_number)
+	}
+}
+
+parameter_types! {
+	pub const ChallengeSlash: Perbill = Perbill::from_percent(10);
+	pub const AvailabilityGateway: &'static str = "https://gateway.test/";
+	pub const DropboxPalletId: PalletId = PalletId(*b"py/dropb");
+	pub const DuplicateUploadFee: Perbill = Perbill::from_percent(10);
+	// Uploads are free unless a test sets a fee.
+	pub static UploadFeePerByte: u64 = 0;
+	pub static FetchesPerLicense: Option<u32> = Some(2);
+	// Uploads pay no rent up front unless a test sets a period.
+	pub static InitialRentPeriod: u64 = 0;
+	pub static CollectionDeposit: u64 = 0;
+	pub static ItemDeposit: u64 = 0;
+}
+
 impl pallet_mydropbox::Config for Test {
 	type Event = Event;
+	type Currency = Balances;
+	type MaxFilesUploaded = ConstU32<5>;
+	type CostPerByte = ConstU64<2>;
+	type FileSizeLimit = ConstU64<250>;
+	type MaxPageSize = ConstU32<3>;
+	type MaxPageScan = ConstU32<4>;
+	type MinProviderStake = ConstU64<1_000>;
+	type UnbondingPeriod = ConstU64<10>;
+	type MaxProvidersPerFile = ConstU32<2>;
+	type MaxFilesPerProvider = ConstU32<3>;
+	type Randomness = TestRandomness;
+	type ChallengePeriod = ConstU64<5>;
+	type ChallengeWindow = ConstU64<3>;
+	type ChallengeSlash = ChallengeSlash;
+	type MaxChallengesPerBlock = ConstU32<4>;
+	type MaxChunkSize = ConstU32<64>;
+	type MaxProofLength = ConstU32<8>;
+	type AvailabilityCheckInterval = ConstU64<4>;
+	type MaxAvailabilityChecks = ConstU32<3>;
+	type AvailabilityGateway = AvailabilityGateway;
+	type UnsignedPriority = ConstU64<100>;
+	type PalletId = DropboxPalletId;
+	type ReplicationPeriod = ConstU64<5>;
+	type MaxReplicationCandidates = ConstU32<4>;
+	type RentPerByte = ConstU64<1>;
+	type RentGracePeriod = ConstU64<10>;
+	type InitialRentPeriod = InitialRentPeriod;
+	type DefaultQuota = ConstU64<10_000>;
+	type UploadFeePerByte = UploadFeePerByte;
+	type DuplicateUploadFee = DuplicateUploadFee;
+	type MaxVersions = ConstU32<2>;
+	type MaxEnvelopeLength = ConstU32<128>;
+	type MaxKeyEnvelopes = ConstU32<4>;
+	type KeyRequestTimeout = ConstU64<5>;
+	type MaxKeyRequestsPerBlock = ConstU32<4>;
+	type Nfts = Uniques;
+	type NftCollection = ConstU32<0>;
+	type MaxOffersPerFile = ConstU32<2>;
+	type MaxAuctionsPerBlock = ConstU32<2>;
+	type MaxAuctionDuration = ConstU64<10>;
+	type TransferOfferTimeout = ConstU64<5>;
+	type MaxTransferOffersPerBlock = ConstU32<2>;
+	type MaxCoOwners = ConstU32<3>;
+	type MaxProposals = ConstU32<2>;
+	type MaxBatchSize = ConstU32<3>;
+	type FetchesPerLicense = FetchesPerLicense;
+	type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: (1..=8).map(|account| (account, 10_000)).chain([(ACCOUNTANT, 10), (POOR, 50)]).collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&pallet_mydropbox::GenesisConfig::default(), &mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+	// Events are not deposited on the genesis block.
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
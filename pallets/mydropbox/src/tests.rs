@@ -1,20 +1,3058 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	merkle, migrations, mock::*, AllDownloadsCount, AllFilesCount, Challenge, Challenges, Error,
+	FileAction, FileFilter, FileRent, FileType, FileUpload, Files, FilesPerUser, License, LicenseKind, LicenseTerms, LicenseTier, OperatorScope, ProviderStatus, QuotaPlan, RentCursor, RentStatus, WeightInfo,
+	KEY_SERVICE_STORAGE_KEY, KEY_TYPE,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchResult,
+	pallet_prelude::{InvalidTransaction, TransactionSource, ValidateUnsigned},
+	traits::{tokens::nonfungibles::Inspect, GetStorageVersion, Hooks, StorageVersion},
+	weights::Weight,
+	BoundedVec, StorageHasher, Twox64Concat,
+};
+use proptest::prelude::*;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt},
+	sr25519, Pair, H256,
+};
+use sp_runtime::traits::{BadOrigin, BlakeTwo256, Hash};
+use std::collections::BTreeSet;
+
+// Files uploaded by the helpers only share contents with files of the same owner, link and size.
+fn content_root(owner: u64, link: u8, file_size: u64) -> H256 {
+	BlakeTwo256::hash_of(&(owner, link, file_size))
+}
+
+fn content_id_of(file_id: H256) -> H256 {
+	TemplateModule::get_file_details(file_id).unwrap().content_id()
+}
+
+fn upload(owner: u64, link: u8, file_type: FileType, cost: u64, file_size: u64) -> H256 {
+	assert_ok!(TemplateModule::upload_file(
+		Origin::signed(owner),
+		[link; 20],
+		true,
+		file_type,
+		cost,
+		file_size,
+		content_root(owner, link, file_size),
+		16,
+		1
+	));
+	*TemplateModule::get_user_file_details(owner).last().unwrap()
+}
+
+// Offers the file and has the recipient accept it.
+fn transfer(owner: u64, file_id: H256, new_owner: u64) -> DispatchResult {
+	TemplateModule::transfer_file(Origin::signed(owner), file_id, new_owner)?;
+	TemplateModule::accept_transfer(Origin::signed(new_owner), file_id)
+}
+
+fn set_accountant() {
+	assert_ok!(TemplateModule::add_accountant(Origin::signed(ACCOUNTANT)));
+}
+
+#[test]
+fn add_accountant_sets_first_signer() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		assert_eq!(TemplateModule::accountant(), Some(ACCOUNTANT));
+
+		// The accountant may call again, anybody else is rejected.
+		assert_ok!(TemplateModule::add_accountant(Origin::signed(ACCOUNTANT)));
+		assert_noop!(
+			TemplateModule::add_accountant(Origin::signed(1)),
+			Error::<Test>::InvalidSigner
+		);
+	});
+}
+
+#[test]
+fn upload_file_stores_file() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!(file.owner, 1);
+		assert_eq!(file.cost, 100);
+		assert_eq!(file.file_size, 200);
+		assert_eq!(TemplateModule::get_user_file_details(1).into_inner(), vec![file_id]);
+		assert_eq!(TemplateModule::all_files_count(), 1);
+		System::assert_last_event(Event::TemplateModule(crate::Event::Uploaded(1, file_id)));
+	});
+}
+
+#[test]
+fn upload_file_fails_when_owner_is_full() {
+	new_test_ext().execute_with(|| {
+		for link in 0..5 {
+			upload(1, link, FileType::Normal, 100, 200);
+		}
+
+		assert_noop!(
+			TemplateModule::upload_file(
+				Origin::signed(1),
+				[5; 20],
+				true,
+				FileType::Normal,
+				100,
+				200,
+				H256::zero(),
+				16,
+				1
+			),
+			Error::<Test>::ExceedMaxFileUploaded
+		);
+	});
+}
+
+#[test]
+fn upload_file_fails_when_quota_is_exceeded() {
+	new_test_ext().execute_with(|| {
+		upload(1, 1, FileType::Normal, 100, 9_000);
+		upload(1, 2, FileType::Normal, 100, 1_000);
+		assert_eq!(TemplateModule::bytes_used(1), 10_000);
+
+		assert_noop!(
+			TemplateModule::upload_file(
+				Origin::signed(1),
+				[3; 20],
+				true,
+				FileType::Normal,
+				100,
+				1,
+				H256::zero(),
+				16,
+				1
+			),
+			Error::<Test>::QuotaExceeded
+		);
+	});
+}
+
+#[test]
+fn buy_plan_raises_quota() {
+	new_test_ext().execute_with(|| {
+		let plan = QuotaPlan { bytes: 5_000, price: 500 };
+		assert_noop!(TemplateModule::set_plan(Origin::signed(1), 0, Some(plan)), BadOrigin);
+		assert_ok!(TemplateModule::set_plan(Origin::root(), 0, Some(plan)));
+		assert_eq!(TemplateModule::quota_plans(0), Some(plan));
+
+		assert_noop!(TemplateModule::buy_plan(Origin::signed(1), 0), Error::<Test>::AccountantNotSet);
+		set_accountant();
+		assert_noop!(TemplateModule::buy_plan(Origin::signed(1), 1), Error::<Test>::PlanNotFound);
+
+		assert_ok!(TemplateModule::buy_plan(Origin::signed(1), 0));
+		assert_eq!(TemplateModule::quota(&1), 15_000);
+		assert_eq!(Balances::free_balance(1), 10_000 - 500);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 500);
+		System::assert_last_event(Event::TemplateModule(crate::Event::PlanBought(1, 0, 15_000)));
+		upload(1, 1, FileType::Normal, 100, 15_000);
+
+		// A withdrawn plan can no longer be bought, but bytes already bought are kept.
+		assert_ok!(TemplateModule::set_plan(Origin::root(), 0, None));
+		assert_noop!(TemplateModule::buy_plan(Origin::signed(2), 0), Error::<Test>::PlanNotFound);
+		assert_eq!(TemplateModule::quota(&1), 15_000);
+	});
+}
+
+#[test]
+fn transfer_file_moves_bytes_between_quotas() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 6_000);
+		upload(2, 2, FileType::Normal, 100, 5_000);
+
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::QuotaExceeded);
+
+		assert_ok!(transfer(1, file_id, 3));
+		assert_eq!(TemplateModule::bytes_used(1), 0);
+		assert_eq!(TemplateModule::bytes_used(3), 6_000);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn upload_file_fails_on_file_count_overflow() {
+	new_test_ext().execute_with(|| {
+		AllFilesCount::<Test>::put(u64::MAX);
+
+		assert_noop!(
+			TemplateModule::upload_file(
+				Origin::signed(1),
+				[1; 20],
+				true,
+				FileType::Normal,
+				100,
+				200,
+				H256::zero(),
+				16,
+				1
+			),
+			Error::<Test>::FileCountOverflow
+		);
+	});
+}
+
+#[test]
+fn download_small_normal_file_charges_cost() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 250);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		assert_eq!(Balances::free_balance(2), 10_000 - 100);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 100);
+		assert_eq!(TemplateModule::get_download_details(file_id, 0).into_inner(), vec![2]);
+		assert_eq!(TemplateModule::all_downloads_count(), 1);
+		System::assert_last_event(Event::TemplateModule(crate::Event::Downloaded(2, file_id, 0, 100)));
+	});
+}
+
+#[test]
+fn download_large_normal_file_charges_per_extra_byte() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 300);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		// 50 bytes over the limit at 2 per byte.
+		let total_cost = 100 + 50 * 2;
+		assert_eq!(Balances::free_balance(2), 10_000 - total_cost);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + total_cost);
+		System::assert_last_event(Event::TemplateModule(crate::Event::Downloaded(
+			2, file_id, 0, total_cost,
+		)));
+	});
+}
+
+#[test]
+fn download_privileged_file_ignores_size() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Privileged, 100, 10_000);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		assert_eq!(Balances::free_balance(2), 10_000 - 100);
+		System::assert_last_event(Event::TemplateModule(crate::Event::Downloaded(2, file_id, 0, 100)));
+	});
+}
+
+#[test]
+fn download_file_fails_for_unknown_file() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), H256::repeat_byte(1), None, LicenseTier::Personal),
+			Error::<Test>::FileNotFound
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_when_not_allowed() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		assert_ok!(TemplateModule::upload_file(
+			Origin::signed(1),
+			[1; 20],
+			false,
+			FileType::Normal,
+			100,
+			200,
+			H256::zero(),
+			16,
+			1
+		));
+		let file_id = TemplateModule::get_user_file_details(1)[0];
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::FileNotAllowedToDownload
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_when_already_downloaded() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::AlreadyDownloaded
+		);
+	});
+}
+
+#[test]
+fn download_file_rejects_repeat_downloader_out_of_order() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		assert_ok!(TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal));
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal),
+			Error::<Test>::AlreadyDownloaded
+		);
+		assert_eq!(TemplateModule::get_download_details(file_id, 0).into_inner(), vec![2, 3]);
+	});
+}
+
+#[test]
+fn download_file_fails_when_downloaders_are_full() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		for downloader in 2..=6 {
+			assert_ok!(TemplateModule::download_file(Origin::signed(downloader), file_id, None, LicenseTier::Personal));
+		}
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(7), file_id, None, LicenseTier::Personal),
+			Error::<Test>::ExceedMaxFileDownload
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_on_cost_overflow() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, u64::MAX);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::InvalidOperation
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_without_enough_balance() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(POOR), file_id, None, LicenseTier::Personal),
+			Error::<Test>::NotEnoughBalance
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_without_accountant() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Privileged, 100, 200);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::AccountantNotSet
+		);
+	});
+}
+
+#[test]
+fn download_file_fails_on_download_count_overflow() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		AllDownloadsCount::<Test>::put(u64::MAX);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::FileDownloadCountOverflow
+		);
+	});
+}
+
+#[test]
+fn transfer_file_moves_ownership() {
+	new_test_ext().execute_with(|| {
+		let kept = upload(1, 1, FileType::Normal, 100, 200);
+		let file_id = upload(1, 2, FileType::Normal, 100, 200);
+
+		// The recipient has to accept the file.
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 1);
+		assert_ok!(TemplateModule::accept_transfer(Origin::signed(2), file_id));
+
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 2);
+		assert_eq!(TemplateModule::get_user_file_details(1).into_inner(), vec![kept]);
+		assert_eq!(TemplateModule::get_user_file_details(2).into_inner(), vec![file_id]);
+		assert_eq!(TemplateModule::all_files_count(), 2);
+		System::assert_last_event(Event::TemplateModule(crate::Event::Transfered(1, 2, file_id)));
+	});
+}
+
+#[test]
+fn transfer_file_fails_for_unknown_file() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::transfer_file(Origin::signed(1), H256::repeat_byte(1), 2),
+			Error::<Test>::FileNotFound
+		);
+	});
+}
+
+#[test]
+fn transfer_file_fails_for_non_owner() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+
+		assert_noop!(
+			TemplateModule::transfer_file(Origin::signed(2), file_id, 3),
+			Error::<Test>::InvalidSigner
+		);
+	});
+}
+
+#[test]
+fn transfer_file_fails_when_missing_from_owner_list() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		FilesPerUser::<Test>::remove(1);
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::FileDoesntExist);
+	});
+}
+
+#[test]
+fn transfer_file_fails_when_new_owner_is_full() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		for link in 10..15 {
+			upload(2, link, FileType::Normal, 100, 200);
+		}
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::ExceedMaxFileUploaded);
+	});
+}
+
+#[test]
+fn failed_download_leaves_funds_and_downloads_unchanged() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		for downloader in 2..=6 {
+			assert_ok!(TemplateModule::download_file(Origin::signed(downloader), file_id, None, LicenseTier::Personal));
+		}
+		let downloaders = TemplateModule::get_download_details(file_id, 0);
+		let accountant_balance = Balances::free_balance(ACCOUNTANT);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(7), file_id, None, LicenseTier::Personal),
+			Error::<Test>::ExceedMaxFileDownload
+		);
+
+		assert_eq!(Balances::free_balance(7), 10_000);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), accountant_balance);
+		assert_eq!(TemplateModule::get_download_details(file_id, 0), downloaders);
+		assert_eq!(TemplateModule::all_downloads_count(), 5);
+	});
+}
+
+#[test]
+fn failed_download_on_count_overflow_does_not_charge() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		AllDownloadsCount::<Test>::put(u64::MAX);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::FileDownloadCountOverflow
+		);
+
+		assert_eq!(Balances::free_balance(2), 10_000);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10);
+		assert!(TemplateModule::get_download_details(file_id, 0).is_empty());
+	});
+}
+
+#[test]
+fn failed_transfer_keeps_file_with_owner() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		let full: Vec<H256> = (10..15).map(|link| upload(2, link, FileType::Normal, 100, 200)).collect();
+
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::ExceedMaxFileUploaded);
+
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 1);
+		assert_eq!(TemplateModule::get_user_file_details(1).into_inner(), vec![file_id]);
+		assert_eq!(TemplateModule::get_user_file_details(2).into_inner(), full);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn transfer_file_to_self_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+
+		assert_noop!(TemplateModule::transfer_file(Origin::signed(1), file_id, 1), Error::<Test>::InvalidOperation);
+
+		assert_eq!(TemplateModule::get_user_file_details(1).into_inner(), vec![file_id]);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn list_files_pages_through_all_files() {
+	new_test_ext().execute_with(|| {
+		for link in 0..5 {
+			upload(1, link, FileType::Normal, 100, 200);
+		}
+		for link in 5..7 {
+			upload(2, link, FileType::Normal, 100, 200);
+		}
+
+		// Pages are capped at `MaxPageSize` and continue after the cursor.
+		let (first, cursor) = TemplateModule::list_files(FileFilter::All, None, 10);
+		assert_eq!(first.len(), 3);
+		assert_eq!(cursor, first.last().map(|f| f.0));
+		let (second, cursor) = TemplateModule::list_files(FileFilter::All, cursor, 3);
+		let (third, cursor) = TemplateModule::list_files(FileFilter::All, cursor, 3);
+		assert_eq!(second.len(), 3);
+		assert_eq!(third.len(), 1);
+		assert_eq!(cursor, None);
+
+		let mut listed: Vec<H256> =
+			first.iter().chain(&second).chain(&third).map(|(id, _)| *id).collect();
+		listed.sort();
+		listed.dedup();
+		assert_eq!(listed.len(), 7);
+	});
+}
+
+#[test]
+fn list_files_filters_by_owner_type_and_price() {
+	new_test_ext().execute_with(|| {
+		let cheap = upload(1, 1, FileType::Normal, 10, 200);
+		let privileged = upload(1, 2, FileType::Privileged, 500, 200);
+		let expensive = upload(2, 3, FileType::Normal, 1_000, 200);
+
+		// Owner pages are sorted by file id.
+		let mut mine = vec![cheap, privileged];
+		mine.sort();
+		let (owned, _) = TemplateModule::list_files(FileFilter::Owner(1), None, 3);
+		assert_eq!(owned.iter().map(|(id, _)| *id).collect::<Vec<_>>(), mine);
+		let (after_cursor, _) = TemplateModule::list_files(FileFilter::Owner(1), Some(mine[0]), 3);
+		assert_eq!(after_cursor.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![mine[1]]);
+
+		let (by_type, _) = TemplateModule::list_files(FileFilter::Type(FileType::Privileged), None, 3);
+		assert_eq!(by_type.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![privileged]);
+
+		let mut by_price = TemplateModule::list_files(FileFilter::PriceRange(100, 1_000), None, 3)
+			.0
+			.into_iter()
+			.map(|(id, _)| id)
+			.collect::<Vec<_>>();
+		by_price.sort();
+		let mut expected = vec![privileged, expensive];
+		expected.sort();
+		assert_eq!(by_price, expected);
+	});
+}
+
+#[test]
+fn list_files_examines_a_bounded_number_of_files_per_page() {
+	new_test_ext().execute_with(|| {
+		for link in 0..5 {
+			upload(1, link, FileType::Normal, 100, 200);
+		}
+		let privileged = upload(2, 5, FileType::Privileged, 100, 200);
+
+		// Each page examines at most `MaxPageScan` files, so the only match may take two pages.
+		let filter = FileFilter::Type(FileType::Privileged);
+		let (first, cursor) = TemplateModule::list_files(filter.clone(), None, 3);
+		assert!(first.len() <= 1);
+		assert!(cursor.is_some());
+		let (second, cursor) = TemplateModule::list_files(filter, cursor, 3);
+		assert_eq!(cursor, None);
+
+		let listed: Vec<H256> = first.iter().chain(&second).map(|(id, _)| *id).collect();
+		assert_eq!(listed, vec![privileged]);
+	});
+}
+
+#[test]
+fn owner_pages_survive_transfers_between_pages() {
+	new_test_ext().execute_with(|| {
+		let mut mine: Vec<H256> = (0..5).map(|link| upload(1, link, FileType::Normal, 100, 200)).collect();
+		mine.sort();
+
+		let (first, cursor) = TemplateModule::list_files(FileFilter::Owner(1), None, 2);
+		assert_eq!(first.iter().map(|(id, _)| *id).collect::<Vec<_>>(), mine[..2].to_vec());
+
+		// Moving a file out of the first page reorders the owner list, but the next page still
+		// continues with the files that were not listed yet.
+		assert_ok!(transfer(1, mine[0], 2));
+		let (second, _) = TemplateModule::list_files(FileFilter::Owner(1), cursor, 3);
+		assert_eq!(second.iter().map(|(id, _)| *id).collect::<Vec<_>>(), mine[2..].to_vec());
+	});
+}
+
+fn register_provider(provider: u64, capacity: u64) {
+	assert_ok!(TemplateModule::register_provider(Origin::signed(provider), 1_000, capacity, 1));
+}
+
+// Asks the provider to store the file, and has it accept.
+fn assign(owner: u64, file_id: H256, provider: u64) -> DispatchResult {
+	TemplateModule::assign_provider(Origin::signed(owner), file_id, provider)?;
+	TemplateModule::fulfill_replication(Origin::signed(provider), content_id_of(file_id))
+}
+
+#[test]
+fn register_provider_reserves_stake() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+
+		let provider = TemplateModule::providers(5).unwrap();
+		assert_eq!(provider.stake, 1_000);
+		assert_eq!(provider.capacity, 1_000);
+		assert_eq!(provider.status, ProviderStatus::Active);
+		assert_eq!(Balances::reserved_balance(5), 1_000);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ProviderRegistered(5, 1_000)));
+
+		assert_noop!(
+			TemplateModule::register_provider(Origin::signed(5), 1_000, 1_000, 1),
+			Error::<Test>::ProviderAlreadyRegistered
+		);
+		assert_noop!(
+			TemplateModule::register_provider(Origin::signed(6), 999, 1_000, 1),
+			Error::<Test>::InsufficientStake
+		);
+	});
+}
+
+#[test]
+fn update_provider_changes_capacity_and_price() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::update_provider(Origin::signed(5), 1_000, 2),
+			Error::<Test>::ProviderNotFound
+		);
+		register_provider(5, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+		assert_ok!(assign(1, file_id, 5));
+
+		assert_noop!(
+			TemplateModule::update_provider(Origin::signed(5), 300, 2),
+			Error::<Test>::CapacityBelowUsage
+		);
+		assert_ok!(TemplateModule::update_provider(Origin::signed(5), 400, 2));
+
+		let provider = TemplateModule::providers(5).unwrap();
+		assert_eq!((provider.capacity, provider.price_per_byte), (400, 2));
+		System::assert_last_event(Event::TemplateModule(crate::Event::ProviderUpdated(5, 400, 2)));
+	});
+}
+
+#[test]
+fn assign_provider_tracks_both_sides() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		register_provider(6, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+
+		assert_ok!(assign(1, file_id, 5));
+		assert_ok!(assign(1, file_id, 6));
+
+		let root = content_id_of(file_id);
+		assert_eq!(TemplateModule::content_providers(root).into_inner(), vec![5, 6]);
+		assert_eq!(TemplateModule::provider_contents(5).into_inner(), vec![root]);
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 400);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ContentAssigned(root, 6)));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn providers_only_host_contents_they_accept() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+		let root = content_id_of(file_id);
+
+		assert_ok!(TemplateModule::assign_provider(Origin::signed(1), file_id, 5));
+		System::assert_last_event(Event::TemplateModule(crate::Event::ReplicationOrderOpened(root, 5, 0, 6)));
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 5),
+			Error::<Test>::ProviderAlreadyRequested
+		);
+
+		// The provider holds nothing it did not accept, so it is never challenged or slashed for it.
+		run_to_block(12);
+		assert!(TemplateModule::content_providers(root).is_empty());
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 0);
+		assert_eq!(Balances::reserved_balance(5), 1_000);
+		assert_noop!(
+			TemplateModule::fulfill_replication(Origin::signed(5), root),
+			Error::<Test>::ReplicationOrderExpired
+		);
+
+		// A lapsed request can be made again, and the provider is assigned once it accepts.
+		assert_ok!(TemplateModule::assign_provider(Origin::signed(1), file_id, 5));
+		assert_ok!(TemplateModule::fulfill_replication(Origin::signed(5), root));
+		System::assert_has_event(Event::TemplateModule(crate::Event::ContentAssigned(root, 5)));
+		assert_eq!(TemplateModule::content_providers(root).into_inner(), vec![5]);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn assign_provider_checks_owner_provider_and_bounds() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 500);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+		let large = upload(1, 2, FileType::Normal, 100, 600);
+
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(2), file_id, 5),
+			Error::<Test>::InvalidSigner
+		);
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 6),
+			Error::<Test>::ProviderNotFound
+		);
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), large, 5),
+			Error::<Test>::NotEnoughCapacity
+		);
+
+		assert_ok!(assign(1, file_id, 5));
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 5),
+			Error::<Test>::NotEnoughCapacity
+		);
+		assert_ok!(TemplateModule::update_provider(Origin::signed(5), 10_000, 1));
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 5),
+			Error::<Test>::ProviderAlreadyAssigned
+		);
+
+		register_provider(6, 10_000);
+		register_provider(7, 10_000);
+		assert_ok!(assign(1, file_id, 6));
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 7),
+			Error::<Test>::ExceedMaxProvidersPerFile
+		);
+
+		for link in 3..5 {
+			let file_id = upload(1, link, FileType::Normal, 100, 10);
+			assert_ok!(assign(1, file_id, 5));
+		}
+		let one_too_many = upload(2, 9, FileType::Normal, 100, 10);
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(2), one_too_many, 5),
+			Error::<Test>::ExceedMaxFilesPerProvider
+		);
+	});
+}
+
+#[test]
+fn unassign_provider_by_owner_or_provider() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		register_provider(6, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+		assert_ok!(assign(1, file_id, 5));
+		assert_ok!(assign(1, file_id, 6));
+
+		assert_noop!(
+			TemplateModule::unassign_provider(Origin::signed(2), file_id, 5),
+			Error::<Test>::InvalidSigner
+		);
+		assert_ok!(TemplateModule::unassign_provider(Origin::signed(1), file_id, 5));
+		assert_ok!(TemplateModule::unassign_provider(Origin::signed(6), file_id, 6));
+		assert_noop!(
+			TemplateModule::unassign_provider(Origin::signed(1), file_id, 6),
+			Error::<Test>::ProviderNotAssigned
+		);
+
+		assert!(TemplateModule::content_providers(content_id_of(file_id)).is_empty());
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 0);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ContentUnassigned(content_id_of(file_id), 6)));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn deregistered_provider_withdraws_after_unbonding() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 400);
+		assert_ok!(assign(1, file_id, 5));
+
+		assert_noop!(
+			TemplateModule::withdraw_stake(Origin::signed(5)),
+			Error::<Test>::ProviderNotUnbonding
+		);
+		assert_ok!(TemplateModule::deregister_provider(Origin::signed(5)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::ProviderUnbonding(5, 11)));
+		assert!(TemplateModule::content_providers(content_id_of(file_id)).is_empty());
+		assert!(TemplateModule::provider_contents(5).is_empty());
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// An unbonding provider cannot take new files or leave twice.
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(1), file_id, 5),
+			Error::<Test>::ProviderNotActive
+		);
+		assert_noop!(
+			TemplateModule::deregister_provider(Origin::signed(5)),
+			Error::<Test>::ProviderNotActive
+		);
+
+		System::set_block_number(10);
+		assert_noop!(
+			TemplateModule::withdraw_stake(Origin::signed(5)),
+			Error::<Test>::ProviderStillUnbonding
+		);
+
+		System::set_block_number(11);
+		assert_ok!(TemplateModule::withdraw_stake(Origin::signed(5)));
+		assert_eq!(TemplateModule::providers(5), None);
+		assert_eq!(Balances::reserved_balance(5), 0);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ProviderDeregistered(5, 1_000)));
+	});
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let next = System::block_number() + 1;
+		System::set_block_number(next);
+		TemplateModule::on_initialize(next);
+	}
+}
+
+fn file_chunks() -> Vec<Vec<u8>> {
+	(0..5u8).map(|i| vec![i; 16]).collect()
+}
+
+fn file_leaves() -> Vec<H256> {
+	file_chunks().iter().map(|chunk| merkle::hash_leaf(chunk)).collect()
+}
+
+// Uploads a file with a content root and assigns it to a freshly registered provider 5.
+fn hosted_file() -> H256 {
+	register_provider(5, 1_000);
+	assert_ok!(TemplateModule::upload_file(
+		Origin::signed(1),
+		[1; 20],
+		true,
+		FileType::Normal,
+		100,
+		80,
+		merkle::root(&file_leaves()),
+		16,
+		1
+	));
+	let file_id = *TemplateModule::get_user_file_details(1).last().unwrap();
+	assert_ok!(assign(1, file_id, 5));
+	file_id
+}
+
+#[test]
+fn merkle_proofs_verify_for_every_leaf() {
+	for count in 1..=9u8 {
+		let leaves: Vec<H256> = (0..count).map(|i| merkle::hash_leaf(&[i])).collect();
+		let root = merkle::root(&leaves);
+
+		for index in 0..count as usize {
+			let proof = merkle::proof(&leaves, index);
+			assert!(merkle::verify(&root, leaves[index], index as u32, count as u32, &proof));
+
+			let wrong_leaf = merkle::hash_leaf(&[u8::MAX]);
+			assert!(!merkle::verify(&root, wrong_leaf, index as u32, count as u32, &proof));
+			assert!(!merkle::verify(&root, leaves[index], count as u32, count as u32, &proof));
+		}
+	}
+}
+
+#[test]
+fn upload_file_rejects_invalid_chunk_size() {
+	new_test_ext().execute_with(|| {
+		for chunk_size in [0, 65] {
+			assert_noop!(
+				TemplateModule::upload_file(
+					Origin::signed(1),
+					[1; 20],
+					true,
+					FileType::Normal,
+					100,
+					200,
+					H256::zero(),
+					chunk_size,
+					1
+				),
+				Error::<Test>::InvalidChunkSize
+			);
+		}
+	});
+}
+
+#[test]
+fn file_chunks_verify_against_content_root() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!(file.content_root, merkle::root(&file_leaves()));
+		assert_eq!(file.chunk_count(), 5);
+
+		let leaves = file_leaves();
+		for (index, chunk) in file_chunks().iter().enumerate() {
+			let proof = merkle::proof(&leaves, index);
+			assert!(TemplateModule::verify_file_chunk(&file_id, index as u32, chunk, &proof));
+			assert!(!TemplateModule::verify_file_chunk(&file_id, index as u32, &[9; 16], &proof));
+			assert!(!TemplateModule::verify_file_chunk(&H256::zero(), index as u32, chunk, &proof));
+		}
+
+		// A chunk longer than the file's chunk size never verifies.
+		assert!(!file.verify_chunk(0, &[0; 17], &merkle::proof(&leaves, 0)));
+	});
+}
+
+#[test]
+fn challenge_is_passed_with_valid_proof() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+
+		run_to_block(4);
+		assert_eq!(TemplateModule::challenges(5), None);
+		run_to_block(5);
+		let challenge = TemplateModule::challenges(5).unwrap();
+		assert_eq!(challenge.content_id, content_id_of(file_id));
+		assert_eq!(challenge.deadline, 8);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ChallengeIssued(
+			5,
+			content_id_of(file_id),
+			challenge.chunk_index,
+			8,
+		)));
+
+		let index = challenge.chunk_index as usize;
+		let chunk = file_chunks()[index].clone().try_into().unwrap();
+		let proof = merkle::proof(&file_leaves(), index).try_into().unwrap();
+		assert_ok!(TemplateModule::respond_to_challenge(Origin::signed(5), chunk, proof));
+
+		assert_eq!(TemplateModule::challenges(5), None);
+		assert_eq!(TemplateModule::providers(5).unwrap().stake, 1_000);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ChallengePassed(5, content_id_of(file_id))));
+
+		// The answered challenge does not expire later on.
+		run_to_block(9);
+		assert_eq!(Balances::reserved_balance(5), 1_000);
+	});
+}
+
+#[test]
+fn invalid_proof_slashes_provider() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		run_to_block(5);
+		let index = TemplateModule::challenges(5).unwrap().chunk_index as usize;
+
+		let wrong_chunk = vec![u8::MAX; 16].try_into().unwrap();
+		let proof = merkle::proof(&file_leaves(), index).try_into().unwrap();
+		assert_ok!(TemplateModule::respond_to_challenge(Origin::signed(5), wrong_chunk, proof));
+
+		assert_eq!(TemplateModule::challenges(5), None);
+		assert_eq!(TemplateModule::providers(5).unwrap().stake, 900);
+		assert_eq!(Balances::reserved_balance(5), 900);
+		let root = content_id_of(file_id);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ChallengeFailed(5, root)));
+		System::assert_has_event(Event::TemplateModule(crate::Event::ProviderSlashed(5, 100)));
+
+		// The provider is dropped from the file, which has no fund to pay for a replacement.
+		assert!(TemplateModule::content_providers(root).is_empty());
+		assert!(TemplateModule::replication_orders(root).is_empty());
+		System::assert_last_event(Event::TemplateModule(crate::Event::ContentUnassigned(root, 5)));
+	});
+}
+
+#[test]
+fn missed_challenge_slashes_provider() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		let issuance = Balances::total_issuance();
+
+		// The provider can still answer during the deadline block.
+		run_to_block(8);
+		assert!(TemplateModule::challenges(5).is_some());
+
+		run_to_block(9);
+		assert_eq!(TemplateModule::challenges(5), None);
+		assert_eq!(TemplateModule::providers(5).unwrap().stake, 900);
+		assert_eq!(Balances::total_issuance(), issuance - 100);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ChallengeMissed(5, content_id_of(file_id))));
+	});
+}
+
+#[test]
+fn unassigned_provider_is_not_slashed_for_open_challenge() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		run_to_block(5);
+		assert!(TemplateModule::challenges(5).is_some());
+
+		assert_ok!(TemplateModule::unassign_provider(Origin::signed(1), file_id, 5));
+		assert_eq!(TemplateModule::challenges(5), None);
+		run_to_block(9);
+		assert_eq!(TemplateModule::providers(5).unwrap().stake, 1_000);
+		assert_eq!(Balances::reserved_balance(5), 1_000);
+	});
+}
+
+#[test]
+fn deregistered_provider_is_not_slashed_for_open_challenge() {
+	new_test_ext().execute_with(|| {
+		hosted_file();
+		run_to_block(5);
+		assert!(TemplateModule::challenges(5).is_some());
+
+		assert_ok!(TemplateModule::deregister_provider(Origin::signed(5)));
+		assert_eq!(TemplateModule::challenges(5), None);
+		run_to_block(9);
+		assert_eq!(TemplateModule::providers(5).unwrap().stake, 1_000);
+	});
+}
+
+#[test]
+fn respond_to_challenge_requires_open_challenge() {
+	new_test_ext().execute_with(|| {
+		hosted_file();
+
+		assert_noop!(
+			TemplateModule::respond_to_challenge(
+				Origin::signed(5),
+				vec![0; 16].try_into().unwrap(),
+				Vec::new().try_into().unwrap()
+			),
+			Error::<Test>::ChallengeNotFound
+		);
+	});
+}
+
+#[test]
+fn withdraw_stake_waits_for_open_challenge() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		assert_ok!(TemplateModule::deregister_provider(Origin::signed(5)));
+		let content_id = content_id_of(file_id);
+		Challenges::<Test>::insert(5, Challenge::<Test> { content_id, chunk_index: 0, deadline: 20 });
+
+		System::set_block_number(11);
+		assert_noop!(
+			TemplateModule::withdraw_stake(Origin::signed(5)),
+			Error::<Test>::ChallengePending
+		);
+	});
+}
+
+#[test]
+fn empty_files_are_not_challenged() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 0);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().chunk_count(), 0);
+		assert_ok!(assign(1, file_id, 5));
+
+		run_to_block(5);
+		assert_eq!(TemplateModule::challenges(5), None);
+	});
+}
+
+fn upload_replicated(owner: u64, link: u8, replication_factor: u32) -> H256 {
+	assert_ok!(TemplateModule::upload_file(
+		Origin::signed(owner),
+		[link; 20],
+		true,
+		FileType::Normal,
+		100,
+		100,
+		content_root(owner, link, 100),
+		16,
+		replication_factor
+	));
+	*TemplateModule::get_user_file_details(owner).last().unwrap()
+}
+
+#[test]
+fn upload_file_rejects_invalid_replication_factor() {
+	new_test_ext().execute_with(|| {
+		for replication_factor in [0, 3] {
+			assert_noop!(
+				TemplateModule::upload_file(
+					Origin::signed(1),
+					[1; 20],
+					true,
+					FileType::Normal,
+					100,
+					100,
+					H256::zero(),
+					16,
+					replication_factor
+				),
+				Error::<Test>::InvalidReplicationFactor
+			);
+		}
+	});
+}
+
+#[test]
+fn fund_file_moves_balance_to_pallet_account() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload_replicated(1, 1, 2);
+
+		assert_noop!(
+			TemplateModule::fund_file(Origin::signed(2), H256::repeat_byte(1), 500),
+			Error::<Test>::FileNotFound
+		);
+		assert_ok!(TemplateModule::fund_file(Origin::signed(2), file_id, 500));
+
+		assert_eq!(TemplateModule::storage_funds(content_id_of(file_id)), 500);
+		assert_eq!(Balances::free_balance(TemplateModule::account_id()), 500);
+		assert_eq!(Balances::free_balance(2), 10_000 - 500);
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileFunded(file_id, 2, 500)));
+	});
+}
+
+#[test]
+fn leaving_provider_is_replaced() {
+	new_test_ext().execute_with(|| {
+		for provider in 5..=7 {
+			register_provider(provider, 1_000);
+		}
+		let file_id = upload_replicated(1, 1, 2);
+		let root = content_id_of(file_id);
+		assert_ok!(TemplateModule::fund_file(Origin::signed(1), file_id, 1_000));
+		assert_ok!(assign(1, file_id, 5));
+
+		// Both missing copies are ordered from the providers other than the one leaving.
+		assert_ok!(TemplateModule::unassign_provider(Origin::signed(5), file_id, 5));
+		let mut ordered: Vec<u64> =
+			TemplateModule::replication_orders(root).iter().map(|order| order.provider).collect();
+		ordered.sort();
+		assert_eq!(ordered, vec![6, 7]);
+		assert_eq!(TemplateModule::storage_funds(root), 800);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ReplicationOrderOpened(
+			root, 6, 100, 6,
+		)));
+
+		assert_ok!(TemplateModule::fulfill_replication(Origin::signed(6), root));
+		assert_eq!(TemplateModule::content_providers(root).into_inner(), vec![6]);
+		assert_eq!(Balances::free_balance(6), 10_000 - 1_000 + 100);
+		System::assert_last_event(Event::TemplateModule(crate::Event::ReplicationFulfilled(root, 6, 100)));
+		assert_noop!(
+			TemplateModule::fulfill_replication(Origin::signed(6), root),
+			Error::<Test>::ReplicationOrderNotFound
+		);
+
+		System::set_block_number(7);
+		assert_noop!(
+			TemplateModule::fulfill_replication(Origin::signed(7), root),
+			Error::<Test>::ReplicationOrderExpired
+		);
+
+		// The expired order is refunded and handed to another provider.
+		assert_ok!(TemplateModule::replicate(Origin::signed(2), file_id));
+		System::assert_has_event(Event::TemplateModule(crate::Event::ReplicationOrderExpired(root, 7)));
+		let orders = TemplateModule::replication_orders(root);
+		assert_eq!(orders.len(), 1);
+		assert_eq!(orders[0].provider, 5);
+		assert_eq!(orders[0].expires_at, 12);
+		assert_eq!(TemplateModule::storage_funds(root), 800);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn replication_is_limited_by_storage_fund() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		register_provider(6, 1_000);
+		let file_id = upload_replicated(1, 1, 2);
+
+		let root = content_id_of(file_id);
+
+		assert_ok!(TemplateModule::replicate(Origin::signed(2), file_id));
+		assert!(TemplateModule::replication_orders(root).is_empty());
+
+		assert_ok!(TemplateModule::fund_file(Origin::signed(1), file_id, 150));
+		assert_ok!(TemplateModule::replicate(Origin::signed(2), file_id));
+		assert_eq!(TemplateModule::replication_orders(root).len(), 1);
+		assert_eq!(TemplateModule::storage_funds(root), 50);
+	});
+}
+
+#[test]
+fn deregistered_provider_is_replaced() {
+	new_test_ext().execute_with(|| {
+		register_provider(5, 1_000);
+		register_provider(6, 1_000);
+		let file_id = upload_replicated(1, 1, 1);
+		assert_ok!(TemplateModule::fund_file(Origin::signed(1), file_id, 100));
+		assert_ok!(assign(1, file_id, 5));
+
+		assert_ok!(TemplateModule::deregister_provider(Origin::signed(5)));
+		let orders = TemplateModule::replication_orders(content_id_of(file_id));
+		assert_eq!(orders.len(), 1);
+		assert_eq!(orders[0].provider, 6);
+	});
+}
+
+#[test]
+fn slashed_provider_is_replaced() {
+	new_test_ext().execute_with(|| {
+		let file_id = hosted_file();
+		register_provider(6, 1_000);
+		assert_ok!(TemplateModule::fund_file(Origin::signed(1), file_id, 80));
+
+		let root = content_id_of(file_id);
+
+		run_to_block(9);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ChallengeMissed(5, root)));
+		assert!(TemplateModule::content_providers(root).is_empty());
+		assert!(TemplateModule::provider_contents(5).is_empty());
+		let orders = TemplateModule::replication_orders(root);
+		assert_eq!(orders.len(), 1);
+		assert_eq!(orders[0].provider, 6);
+		assert_eq!(orders[0].reward, 80);
+	});
+}
+
+#[test]
+fn extend_storage_pays_rent_to_accountant() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_eq!(
+			TemplateModule::file_rent(file_id),
+			Some(RentStatus { paid_through: 1, expired: false })
+		);
+
+		assert_noop!(
+			TemplateModule::extend_storage(Origin::signed(1), file_id, 10),
+			Error::<Test>::AccountantNotSet
+		);
+		set_accountant();
+		assert_noop!(
+			TemplateModule::extend_storage(Origin::signed(1), H256::repeat_byte(1), 10),
+			Error::<Test>::FileNotFound
+		);
+		assert_noop!(
+			TemplateModule::extend_storage(Origin::signed(2), file_id, 10),
+			Error::<Test>::InvalidSigner
+		);
+
+		assert_ok!(TemplateModule::extend_storage(Origin::signed(1), file_id, 10));
+		assert_eq!(Balances::free_balance(1), 10_000 - 1_000);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 1_000);
+		assert_eq!(TemplateModule::file_rent(file_id).unwrap().paid_through, 11);
+		System::assert_last_event(Event::TemplateModule(crate::Event::StorageExtended(file_id, 11)));
+	});
+}
+
+#[test]
+fn uploads_pay_the_first_rent_period() {
+	new_test_ext().execute_with(|| {
+		InitialRentPeriod::set(5);
+		assert_noop!(
+			TemplateModule::upload_file(
+				Origin::signed(1),
+				[1; 20],
+				true,
+				FileType::Normal,
+				100,
+				100,
+				content_root(1, 1, 100),
+				1,
+				1
+			),
+			Error::<Test>::AccountantNotSet
+		);
+
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_eq!(Balances::free_balance(1), 10_000 - 500);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 500);
+		assert_eq!(
+			TemplateModule::file_rent(file_id),
+			Some(RentStatus { paid_through: 6, expired: false })
+		);
+
+		// The file can be bought until the paid period ends.
+		run_to_block(6);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		run_to_block(7);
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal),
+			Error::<Test>::StorageExpired
+		);
+	});
+}
+
+#[test]
+fn overdue_file_cannot_be_downloaded_until_extended() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+
+		System::set_block_number(2);
+		TemplateModule::on_idle(2, Weight::MAX);
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileExpired(file_id, 11)));
+		assert!(TemplateModule::file_rent(file_id).unwrap().expired);
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::StorageExpired
+		);
+
+		assert_ok!(TemplateModule::extend_storage(Origin::signed(1), file_id, 5));
+		assert_eq!(
+			TemplateModule::file_rent(file_id),
+			Some(RentStatus { paid_through: 6, expired: false })
+		);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+	});
+}
+
+#[test]
+fn expired_file_is_removed_after_grace_period() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		register_provider(5, 1_000);
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		let root = content_id_of(file_id);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_ok!(assign(1, file_id, 5));
+		assert_ok!(TemplateModule::fund_file(Origin::signed(1), file_id, 300));
+
+		// The owner is only warned once.
+		System::set_block_number(2);
+		TemplateModule::on_idle(2, Weight::MAX);
+		System::reset_events();
+		TemplateModule::on_idle(2, Weight::MAX);
+		assert!(System::events().is_empty());
+
+		System::set_block_number(11);
+		TemplateModule::on_idle(11, Weight::MAX);
+		assert!(TemplateModule::get_file_details(file_id).is_some());
+
+		System::set_block_number(12);
+		TemplateModule::on_idle(12, u64::MAX);
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileRemoved(file_id)));
+		assert_eq!(TemplateModule::get_file_details(file_id), None);
+		assert_eq!(TemplateModule::file_rent(file_id), None);
+		assert!(TemplateModule::get_user_file_details(1).is_empty());
+		assert!(TemplateModule::get_download_details(file_id, 0).is_empty());
+		assert_eq!(TemplateModule::all_files_count(), 0);
+		assert_eq!(TemplateModule::all_downloads_count(), 0);
+		assert!(TemplateModule::provider_contents(5).is_empty());
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 0);
+		assert_eq!(TemplateModule::storage_funds(root), 0);
+		assert_eq!(TemplateModule::contents(root), None);
+		// What is left of the fund goes to the accountant rather than back to the owner.
+		assert_eq!(Balances::free_balance(1), 10_000 - 300);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 100 + 300);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn on_idle_stops_at_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		upload(1, 1, FileType::Normal, 100, 100);
+		upload(2, 2, FileType::Normal, 100, 100);
+		System::set_block_number(12);
+
+		assert_eq!(TemplateModule::on_idle(12, 0), 0);
+		assert_eq!(Files::<Test>::iter().count(), 2);
+
+		// Each call picks up after the file the previous one stopped at.
+		let one_file = <() as WeightInfo>::collect_expired(1);
+		assert_eq!(TemplateModule::on_idle(12, one_file), one_file);
+		assert_eq!(Files::<Test>::iter().count(), 1);
+		assert_eq!(TemplateModule::on_idle(12, one_file), one_file);
+		assert_eq!(Files::<Test>::iter().count(), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn on_idle_starts_over_after_the_last_file() {
+	new_test_ext().execute_with(|| {
+		upload(1, 1, FileType::Normal, 100, 100);
+		upload(2, 2, FileType::Normal, 100, 100);
+		let last = FileRent::<Test>::iter_keys().last().unwrap();
+		RentCursor::<Test>::put(last);
+		System::set_block_number(12);
+
+		// Nothing is left after the cursor, so the walk wraps around instead of stopping there.
+		let one_file = <() as WeightInfo>::collect_expired(1);
+		assert_eq!(TemplateModule::on_idle(12, one_file), <() as WeightInfo>::collect_expired(0));
+		assert_eq!(RentCursor::<Test>::get(), None);
+		assert_eq!(Files::<Test>::iter().count(), 2);
+		assert_eq!(TemplateModule::on_idle(12, one_file), one_file);
+		assert_eq!(Files::<Test>::iter().count(), 1);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+fn upload_content(owner: u64, link: u8, content_root: H256, file_size: u64, chunk_size: u32) -> DispatchResult {
+	TemplateModule::upload_file(
+		Origin::signed(owner),
+		[link; 20],
+		true,
+		FileType::Normal,
+		100,
+		file_size,
+		content_root,
+		chunk_size,
+		1,
+	)
+}
+
+#[test]
+fn identical_contents_are_stored_once() {
+	new_test_ext().execute_with(|| {
+		UploadFeePerByte::set(2);
+		register_provider(5, 1_000);
+		let root = H256::repeat_byte(7);
+		let id = merkle::content_id(&root, 100, 16);
+
+		assert_eq!(TemplateModule::upload_fee(&id, 100), 200);
+		assert_ok!(upload_content(1, 1, root, 100, 16));
+		let first = TemplateModule::get_user_file_details(1)[0];
+		assert_eq!(TemplateModule::upload_fee(&id, 100), 20);
+		assert_ok!(upload_content(2, 2, root, 100, 16));
+		let second = TemplateModule::get_user_file_details(2)[0];
+		System::assert_last_event(Event::TemplateModule(crate::Event::ContentShared(second, id, 2)));
+
+		// Each owner keeps its own file, while the contents and their fund are shared.
+		assert_ne!(first, second);
+		assert_eq!(TemplateModule::contents(id).unwrap().refs, 2);
+		assert_eq!(TemplateModule::storage_funds(id), 220);
+		assert_eq!(Balances::free_balance(1), 10_000 - 200);
+		assert_eq!(Balances::free_balance(2), 10_000 - 20);
+		assert_eq!(TemplateModule::bytes_used(2), 100);
+
+		// A provider stores the contents once for both files.
+		assert_ok!(assign(1, first, 5));
+		assert_noop!(
+			TemplateModule::assign_provider(Origin::signed(2), second, 5),
+			Error::<Test>::ProviderAlreadyAssigned
+		);
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 100);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn contents_with_other_sizes_are_stored_apart() {
+	new_test_ext().execute_with(|| {
+		UploadFeePerByte::set(1);
+		let root = H256::repeat_byte(7);
+		let id = merkle::content_id(&root, 100, 16);
+
+		// Registering a root with the wrong sizes first neither blocks nor discounts the real
+		// contents.
+		assert_ok!(upload_content(2, 2, root, 101, 16));
+		assert_eq!(TemplateModule::upload_fee(&id, 100), 100);
+		assert_ok!(upload_content(1, 1, root, 100, 16));
+		assert_ok!(upload_content(3, 3, root, 100, 32));
+		assert_eq!(TemplateModule::contents(id).map(|content| content.refs), Some(1));
+		assert_eq!(TemplateModule::contents(merkle::content_id(&root, 101, 16)).map(|content| content.refs), Some(1));
+		assert_eq!(Balances::free_balance(1), 10_000 - 100);
+
+		assert_noop!(upload_content(1, 1, root, 100, 16), Error::<Test>::FileAlreadyExists);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn contents_are_removed_with_last_file() {
+	new_test_ext().execute_with(|| {
+		UploadFeePerByte::set(1);
+		set_accountant();
+		register_provider(5, 1_000);
+		let root = H256::repeat_byte(7);
+		let id = merkle::content_id(&root, 100, 16);
+		assert_ok!(upload_content(1, 1, root, 100, 16));
+		assert_ok!(upload_content(2, 2, root, 100, 16));
+		let first = TemplateModule::get_user_file_details(1)[0];
+		let second = TemplateModule::get_user_file_details(2)[0];
+		assert_ok!(assign(1, first, 5));
+		assert_ok!(TemplateModule::extend_storage(Origin::signed(2), second, 20));
+
+		// Removing one of the files leaves the contents in place.
+		System::set_block_number(12);
+		TemplateModule::on_idle(12, u64::MAX);
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileRemoved(first)));
+		assert_eq!(TemplateModule::contents(id).unwrap().refs, 1);
+		assert_eq!(TemplateModule::content_providers(id).into_inner(), vec![5]);
+		assert_eq!(TemplateModule::storage_funds(id), 110);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// The last file takes the contents with it, and the accountant gets what is left of the fund.
+		System::set_block_number(32);
+		TemplateModule::on_idle(32, Weight::MAX);
+		System::assert_has_event(Event::TemplateModule(crate::Event::ContentRemoved(id)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileRemoved(second)));
+		assert_eq!(TemplateModule::contents(id), None);
+		assert!(TemplateModule::provider_contents(5).is_empty());
+		assert_eq!(TemplateModule::providers(5).unwrap().used, 0);
+		assert_eq!(TemplateModule::storage_funds(id), 0);
+		assert_eq!(Balances::free_balance(2), 10_000 - 10 - 2_000);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+fn publish(owner: u64, file_id: H256, link: u8, file_size: u64) -> DispatchResult {
+	TemplateModule::publish_version(
+		Origin::signed(owner),
+		file_id,
+		[link; 20],
+		file_size,
+		content_root(owner, link, file_size),
+		16,
+	)
+}
+
+#[test]
+fn publish_version_keeps_history() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		let first = TemplateModule::get_file_details(file_id).unwrap().current_version();
+
+		assert_noop!(publish(2, file_id, 2, 200), Error::<Test>::InvalidSigner);
+		assert_ok!(publish(1, file_id, 2, 200));
+		System::assert_last_event(Event::TemplateModule(crate::Event::VersionPublished(file_id, 1)));
+
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!(file.version, 1);
+		assert_eq!(file.file_link, [2; 20]);
+		assert_eq!(file.file_size, 200);
+		assert_eq!(file.content_root, content_root(1, 2, 200));
+		assert_eq!(TemplateModule::file_versions(file_id).into_inner(), vec![first.clone()]);
+		// Previous versions are still stored, so they count towards the quota.
+		assert_eq!(TemplateModule::bytes_used(1), 300);
+
+		assert_ok!(publish(1, file_id, 3, 300));
+		// A full history drops its oldest version to make room, along with its contents and bytes.
+		assert_ok!(publish(1, file_id, 4, 400));
+		System::assert_has_event(Event::TemplateModule(crate::Event::VersionDropped(file_id, 0)));
+		let versions: Vec<_> = TemplateModule::file_versions(file_id).iter().map(|previous| previous.version).collect();
+		assert_eq!(versions, vec![1, 2]);
+		assert_eq!(TemplateModule::contents(first.content_id()), None);
+		assert_eq!(TemplateModule::bytes_used(1), 900);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// Removing the file releases the contents of every version.
+		System::set_block_number(12);
+		TemplateModule::on_idle(12, u64::MAX);
+		assert_eq!(TemplateModule::get_file_details(file_id), None);
+		assert!(TemplateModule::file_versions(file_id).is_empty());
+		assert_eq!(TemplateModule::contents(first.content_id()), None);
+		assert_eq!(TemplateModule::bytes_used(1), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn dropped_versions_take_their_downloads_with_them() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		// One buyer got the key of the first version, and another is still waiting for it.
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 2, envelope(b"key")));
+		assert_ok!(TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal));
+		assert_eq!(Balances::reserved_balance(3), 100);
+		assert_ok!(publish(1, file_id, 2, 200));
+		assert_ok!(publish(1, file_id, 3, 300));
+
+		assert_ok!(publish(1, file_id, 4, 400));
+		System::assert_has_event(Event::TemplateModule(crate::Event::KeyRequestRefunded(file_id, 3, 100)));
+		System::assert_has_event(Event::TemplateModule(crate::Event::VersionDropped(file_id, 0)));
+		assert!(TemplateModule::get_download_details(file_id, 0).is_empty());
+		assert_eq!(TemplateModule::all_downloads_count(), 0);
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)), None);
+		assert_eq!(TemplateModule::key_requests(file_id, 3), None);
+		assert_eq!(Balances::free_balance(3), 10_000);
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, Some(0), LicenseTier::Personal),
+			Error::<Test>::VersionNotFound
+		);
+		assert_noop!(TemplateModule::rollback_to_version(Origin::signed(1), file_id, 0), Error::<Test>::VersionNotFound);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn rollback_restores_previous_version() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(publish(1, file_id, 2, 200));
+		let published = TemplateModule::get_file_details(file_id).unwrap().current_version();
+
+		assert_noop!(
+			TemplateModule::rollback_to_version(Origin::signed(2), file_id, 0),
+			Error::<Test>::InvalidSigner
+		);
+		assert_noop!(
+			TemplateModule::rollback_to_version(Origin::signed(1), file_id, 5),
+			Error::<Test>::VersionNotFound
+		);
+		assert_ok!(TemplateModule::rollback_to_version(Origin::signed(1), file_id, 0));
+		System::assert_last_event(Event::TemplateModule(crate::Event::RolledBack(file_id, 0)));
+
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!(file.version, 0);
+		assert_eq!(file.file_size, 100);
+		assert_eq!(TemplateModule::file_versions(file_id).into_inner(), vec![published]);
+
+		// Version numbers keep counting up after a rollback.
+		assert_ok!(publish(1, file_id, 3, 300));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().version, 2);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn downloads_are_bought_per_version() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(publish(1, file_id, 2, 100));
+
+		// The current version is bought by default.
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Downloaded(2, file_id, 1, 100)));
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, Some(1), LicenseTier::Personal),
+			Error::<Test>::AlreadyDownloaded
+		);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, Some(0), LicenseTier::Personal));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Downloaded(2, file_id, 0, 100)));
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, Some(7), LicenseTier::Personal),
+			Error::<Test>::VersionNotFound
+		);
+
+		assert_eq!(TemplateModule::get_download_details(file_id, 0).into_inner(), vec![2]);
+		assert_eq!(TemplateModule::get_download_details(file_id, 1).into_inner(), vec![2]);
+		assert_eq!(TemplateModule::all_downloads_count(), 2);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+// Authorises a new keystore key to sign availability reports.
+fn reporter() -> sr25519::Public {
+	let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+	assert_ok!(TemplateModule::set_reporter(Origin::root(), key, true));
+	key
+}
+
+fn report_signature(key: &sr25519::Public, block_number: u64, files: &[H256]) -> sr25519::Signature {
+	let payload = TemplateModule::availability_report_payload(&block_number, files);
+	sp_io::crypto::sr25519_sign(KEY_TYPE, key, &payload).unwrap()
+}
+
+#[test]
+fn file_url_hex_encodes_link() {
+	new_test_ext().execute_with(|| {
+		let url = TemplateModule::file_url(&[0xab; 20]);
+		assert_eq!(url, format!("https://gateway.test/{}", "ab".repeat(20)).into_bytes());
+	});
+}
+
+#[test]
+fn offchain_worker_checks_sampled_links() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		reporter();
+		upload(1, 0xab, FileType::Normal, 100, 200);
+		state.write().expect_request(testing::PendingRequest {
+			method: "GET".into(),
+			uri: format!("https://gateway.test/{}", "ab".repeat(20)),
+			response: Some(b"contents".to_vec()),
+			sent: true,
+			..Default::default()
+		});
+
+		// Links are only checked every `AvailabilityCheckInterval` blocks.
+		TemplateModule::offchain_worker(3);
+		TemplateModule::offchain_worker(4);
+
+		// The link answered, so nothing is reported.
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn unavailable_files_are_reported_unsigned() {
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		System::set_block_number(4);
+		let key = reporter();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		assert_eq!(TemplateModule::local_reporter(), Some(key));
+		assert_ok!(TemplateModule::submit_unavailable(4, vec![file_id], &key));
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		let files: BoundedVec<_, _> = vec![file_id].try_into().unwrap();
+		let signature = report_signature(&key, 4, &files);
+		assert_eq!(
+			tx.call,
+			Call::TemplateModule(crate::Call::report_unavailable {
+				block_number: 4,
+				files: files.clone(),
+				reporter: key,
+				signature: signature.clone(),
+			})
+		);
+
+		assert_noop!(
+			TemplateModule::report_unavailable(Origin::signed(1), 4, files.clone(), key, signature.clone()),
+			BadOrigin
+		);
+		assert_ok!(TemplateModule::report_unavailable(Origin::none(), 4, files, key, signature));
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileUnavailable(file_id)));
+		assert_eq!(TemplateModule::next_availability_report(), 8);
+	});
+}
+
+#[test]
+fn availability_reports_are_validated() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(4);
+		let key = reporter();
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		let report = |block_number, files: Vec<H256>| {
+			let signature = report_signature(&key, block_number, &files);
+			crate::Call::report_unavailable {
+				block_number,
+				files: files.try_into().unwrap(),
+				reporter: key,
+				signature,
+			}
+		};
+		let validate = |call| TemplateModule::validate_unsigned(TransactionSource::External, &call);
+
+		assert_eq!(validate(report(4, vec![])), InvalidTransaction::Call.into());
+		assert_eq!(validate(report(5, vec![file_id])), InvalidTransaction::Future.into());
+		assert!(validate(report(4, vec![file_id])).is_ok());
+
+		// Once a check is reported, reports of it and earlier checks are stale.
+		let files: BoundedVec<_, _> = vec![file_id].try_into().unwrap();
+		let signature = report_signature(&key, 4, &files);
+		assert_ok!(TemplateModule::report_unavailable(Origin::none(), 4, files, key, signature));
+		assert_eq!(validate(report(4, vec![file_id])), InvalidTransaction::Stale.into());
+		System::set_block_number(8);
+		assert!(validate(report(8, vec![file_id])).is_ok());
+	});
+}
+
+#[test]
+fn availability_reports_need_an_authorised_signature() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(4);
+		let key = reporter();
+		let stranger = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+		let file_id = upload(1, 1, FileType::Normal, 100, 200);
+		let files: BoundedVec<_, _> = vec![file_id].try_into().unwrap();
+		let validate = |call| TemplateModule::validate_unsigned(TransactionSource::External, &call);
+
+		// A signature over another report does not verify.
+		let forged = report_signature(&key, 4, &[H256::repeat_byte(1)]);
+		let call = crate::Call::report_unavailable {
+			block_number: 4,
+			files: files.clone(),
+			reporter: key,
+			signature: forged.clone(),
+		};
+		assert_eq!(validate(call), InvalidTransaction::BadProof.into());
+		assert_noop!(
+			TemplateModule::report_unavailable(Origin::none(), 4, files.clone(), key, forged),
+			Error::<Test>::InvalidReport
+		);
+
+		// Keys that were never allowed, or no longer are, cannot report.
+		let signature = report_signature(&stranger, 4, &files);
+		let call = crate::Call::report_unavailable {
+			block_number: 4,
+			files: files.clone(),
+			reporter: stranger,
+			signature: signature.clone(),
+		};
+		assert_eq!(validate(call), InvalidTransaction::BadProof.into());
+		assert_noop!(
+			TemplateModule::report_unavailable(Origin::none(), 4, files.clone(), stranger, signature),
+			Error::<Test>::InvalidReport
+		);
+
+		assert_noop!(TemplateModule::set_reporter(Origin::signed(1), key, false), BadOrigin);
+		assert_ok!(TemplateModule::set_reporter(Origin::root(), key, false));
+		System::assert_last_event(Event::TemplateModule(crate::Event::ReporterSet(key, false)));
+		assert_eq!(TemplateModule::local_reporter(), None);
+		let signature = report_signature(&key, 4, &files);
+		assert_noop!(
+			TemplateModule::report_unavailable(Origin::none(), 4, files, key, signature),
+			Error::<Test>::InvalidReport
+		);
+	});
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+	Upload { who: u64, link: u8, privileged: bool, cost: u64, file_size: u64 },
+	Download { who: u64, file: usize },
+	// `who` of `None` signs as the current owner of the file.
+	Transfer { who: Option<u64>, file: usize, to: u64 },
+}
+
+fn action() -> impl Strategy<Value = Action> {
+	prop_oneof![
+		(1..=8u64, any::<u8>(), any::<bool>(), 0..500u64, 0..1_000u64).prop_map(
+			|(who, link, privileged, cost, file_size)| Action::Upload {
+				who,
+				link,
+				privileged,
+				cost,
+				file_size
+			}
+		),
+		(1..=8u64, any::<usize>()).prop_map(|(who, file)| Action::Download { who, file }),
+		(proptest::option::of(1..=8u64), any::<usize>(), 1..=8u64)
+			.prop_map(|(who, file, to)| Action::Transfer { who, file, to }),
+	]
+}
+
+// Picks an existing file, in a stable order, from an arbitrary index.
+fn pick_file(index: usize) -> Option<H256> {
+	let mut files: Vec<H256> = Files::<Test>::iter_keys().collect();
+	files.sort();
+	(!files.is_empty()).then(|| files[index % files.len()])
+}
+
+fn apply(action: Action, downloaded: &mut BTreeSet<(u64, H256)>) {
+	match action {
+		Action::Upload { who, link, privileged, cost, file_size } => {
+			let file_type = if privileged { FileType::Privileged } else { FileType::Normal };
+			let _ = TemplateModule::upload_file(
+				Origin::signed(who),
+				[link; 20],
+				true,
+				file_type,
+				cost,
+				file_size,
+				BlakeTwo256::hash_of(&(link, file_size)),
+				16,
+				1,
+			);
+		},
+		Action::Download { who, file } => {
+			let file_id = match pick_file(file) {
+				Some(file_id) => file_id,
+				None => return,
+			};
+			let balance = Balances::free_balance(who);
+			let result = TemplateModule::download_file(Origin::signed(who), file_id, None, LicenseTier::Personal);
+
+			if result.is_ok() {
+				assert!(downloaded.insert((who, file_id)), "{} charged twice for a file", who);
+				assert!(Balances::free_balance(who) <= balance);
+			} else {
+				assert_eq!(Balances::free_balance(who), balance);
+			}
+		},
+		Action::Transfer { who, file, to } => {
+			let file_id = match pick_file(file) {
+				Some(file_id) => file_id,
+				None => return,
+			};
+			let who = who.unwrap_or_else(|| TemplateModule::get_file_details(file_id).unwrap().owner);
+			let _ = transfer(who, file_id, to);
+		},
+	}
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(64))]
+
+	#[test]
+	fn storage_invariants_hold_for_random_calls(actions in prop::collection::vec(action(), 1..60)) {
+		new_test_ext().execute_with(|| {
+			set_accountant();
+			let issuance = Balances::total_issuance();
+			let mut downloaded = BTreeSet::new();
+
+			for action in actions {
+				apply(action, &mut downloaded);
+				assert_eq!(TemplateModule::do_try_state(), Ok(()));
+			}
+
+			assert_eq!(Balances::total_issuance(), issuance);
+			assert_eq!(TemplateModule::all_downloads_count(), downloaded.len() as u64);
+		});
+	}
+}
+
+fn envelope(bytes: &[u8]) -> BoundedVec<u8, <Test as crate::Config>::MaxEnvelopeLength> {
+	bytes.to_vec().try_into().unwrap()
+}
+
+#[test]
+fn owner_grants_and_revokes_access_to_encrypted_file() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+
+		assert_noop!(
+			TemplateModule::grant_access(Origin::signed(2), file_id, 3, envelope(b"key")),
+			Error::<Test>::InvalidSigner
+		);
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 3, envelope(b"key")));
+		System::assert_last_event(Event::TemplateModule(crate::Event::AccessGranted(file_id, 3)));
+		assert_eq!(TemplateModule::key_envelope(file_id, 3), Some(envelope(b"key")));
+
+		// Granting again replaces the envelope.
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 3, envelope(b"new key")));
+		assert_eq!(TemplateModule::key_envelope(file_id, 3), Some(envelope(b"new key")));
+
+		assert_ok!(TemplateModule::revoke_access(Origin::signed(1), file_id, 3));
+		System::assert_last_event(Event::TemplateModule(crate::Event::AccessRevoked(file_id, 3)));
+		assert_eq!(TemplateModule::key_envelope(file_id, 3), None);
+		assert_noop!(
+			TemplateModule::revoke_access(Origin::signed(1), file_id, 3),
+			Error::<Test>::EnvelopeNotFound
+		);
+	});
+}
+
+#[test]
+fn key_envelopes_per_file_are_bounded() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		for recipient in 2..=5 {
+			assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, recipient, envelope(b"key")));
+		}
+		assert_noop!(
+			TemplateModule::grant_access(Origin::signed(1), file_id, 6, envelope(b"key")),
+			Error::<Test>::TooManyEnvelopes
+		);
+
+		// Replacing an envelope takes no room, and revoking one frees it.
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 3, envelope(b"new key")));
+		assert_ok!(TemplateModule::revoke_access(Origin::signed(1), file_id, 3));
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 6, envelope(b"key")));
+		assert_eq!(crate::KeyEnvelopeCount::<Test>::get(file_id), 4);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// Removing the file drops its envelopes along with their count.
+		TemplateModule::on_idle(12, u64::MAX);
+		assert_eq!(TemplateModule::get_file_details(file_id), None);
+		assert_eq!(TemplateModule::key_envelope(file_id, 2), None);
+		assert_eq!(crate::KeyEnvelopeCount::<Test>::get(file_id), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn grant_access_requires_encrypted_file() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+
+		assert_noop!(
+			TemplateModule::grant_access(Origin::signed(1), file_id, 3, envelope(b"key")),
+			Error::<Test>::FileNotEncrypted
+		);
+		assert_noop!(
+			TemplateModule::grant_access(Origin::signed(1), H256::zero(), 3, envelope(b"key")),
+			Error::<Test>::FileNotFound
+		);
+	});
+}
+
+#[test]
+fn encrypted_file_is_priced_like_normal_file() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 300);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_eq!(Balances::free_balance(2), 10_000 - (100 + 50 * 2));
+	});
+}
+
+#[test]
+fn encrypted_download_holds_payment_until_key_is_delivered() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		System::assert_has_event(Event::TemplateModule(crate::Event::KeyRequested(file_id, 2, 6)));
+		assert_eq!(Balances::reserved_balance(2), 100);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10);
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::AlreadyDownloaded
+		);
+
+		// The owner delivering the key by hand settles the request too.
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 2, envelope(b"key")));
+		System::assert_last_event(Event::TemplateModule(crate::Event::KeyRequestFulfilled(file_id, 2)));
+		assert_eq!(TemplateModule::key_requests(file_id, 2), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 10_000 - 100);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 100);
+
+		// Buyers who already hold the key pay right away.
+		assert_ok!(TemplateModule::grant_access(Origin::signed(1), file_id, 3, envelope(b"key")));
+		assert_ok!(TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal));
+		assert_eq!(TemplateModule::key_requests(file_id, 3), None);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 200);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn undelivered_key_request_is_refunded() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+
+		// The key can still be delivered in the last block before the deadline passes.
+		TemplateModule::on_initialize(6);
+		assert!(TemplateModule::key_requests(file_id, 2).is_some());
+
+		TemplateModule::on_initialize(7);
+		System::assert_last_event(Event::TemplateModule(crate::Event::KeyRequestRefunded(file_id, 2, 100)));
+		assert_eq!(TemplateModule::key_requests(file_id, 2), None);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 10_000);
+		assert!(TemplateModule::get_download_details(file_id, 0).is_empty());
+		assert_eq!(AllDownloadsCount::<Test>::get(), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// The buyer can buy the file again.
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+	});
+}
+
+#[test]
+fn offchain_worker_delivers_bought_keys() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		set_accountant();
+		let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+		assert_ok!(TemplateModule::set_delivery_key(Origin::signed(1), key));
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		let expect_key_request = || {
+			state.write().expect_request(testing::PendingRequest {
+				method: "GET".into(),
+				uri: format!("https://keys.test/{:x}/{}", file_id, "0200000000000000"),
+				response: Some(b"sealed".to_vec()),
+				sent: true,
+				..Default::default()
+			})
+		};
+
+		// Nothing is fetched until the node is told where its key service is.
+		TemplateModule::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, KEY_SERVICE_STORAGE_KEY, b"https://keys.test/");
+
+		// Only requests bought in the block, or half a timeout before it, are looked at.
+		TemplateModule::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+		expect_key_request();
+		TemplateModule::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		let call = match tx.call {
+			Call::TemplateModule(call) => call,
+			call => panic!("unexpected call {:?}", call),
+		};
+		assert!(TemplateModule::validate_unsigned(TransactionSource::External, &call).is_ok());
+		let (buyer, sealed, signature) = match call {
+			crate::Call::fulfill_key_request { buyer, envelope, signature, .. } => (buyer, envelope, signature),
+			call => panic!("unexpected call {:?}", call),
+		};
+		assert_eq!((buyer, sealed.clone()), (2, envelope(b"sealed")));
+
+		// A request still open half a timeout later is tried once more.
+		expect_key_request();
+		TemplateModule::offchain_worker(3);
+		assert_eq!(pool_state.read().transactions.len(), 1);
+
+		assert_ok!(TemplateModule::fulfill_key_request(Origin::none(), file_id, 2, sealed, signature));
+		assert_eq!(TemplateModule::key_envelope(file_id, 2), Some(envelope(b"sealed")));
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 100);
+	});
+}
+
+#[test]
+fn key_deliveries_are_validated() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let owner_key = sr25519::Pair::from_seed(&[1; 32]);
+		let other_key = sr25519::Pair::from_seed(&[2; 32]);
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		let delivery = |pair: &sr25519::Pair, buyer| {
+			let payload = TemplateModule::key_delivery_payload(&file_id, &buyer, b"key");
+			crate::Call::fulfill_key_request {
+				file_id,
+				buyer,
+				envelope: envelope(b"key"),
+				signature: pair.sign(&payload),
+			}
+		};
+		let validate = |call| TemplateModule::validate_unsigned(TransactionSource::External, &call);
+
+		// Nothing was bought yet.
+		assert_eq!(validate(delivery(&owner_key, 2)), InvalidTransaction::Stale.into());
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		// The owner has not set a delivery key.
+		assert_eq!(validate(delivery(&owner_key, 2)), InvalidTransaction::BadProof.into());
+
+		assert_ok!(TemplateModule::set_delivery_key(Origin::signed(1), owner_key.public()));
+		assert_eq!(validate(delivery(&other_key, 2)), InvalidTransaction::BadProof.into());
+		assert!(validate(delivery(&owner_key, 2)).is_ok());
+
+		let signature = other_key.sign(&TemplateModule::key_delivery_payload(&file_id, &2, b"key"));
+		assert_noop!(
+			TemplateModule::fulfill_key_request(Origin::signed(1), file_id, 2, envelope(b"key"), signature.clone()),
+			BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::fulfill_key_request(Origin::none(), file_id, 2, envelope(b"key"), signature),
+			Error::<Test>::InvalidKeyDelivery
+		);
+	});
+}
+
+fn nft_owner(file_id: H256) -> Option<u64> {
+	<Uniques as Inspect<u64>>::owner(&0, &file_id)
+}
+
+#[test]
+fn files_are_minted_and_transferred_as_nfts() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_eq!(<Uniques as Inspect<u64>>::collection_owner(&0), Some(TemplateModule::nft_account()));
+		assert_eq!(nft_owner(file_id), Some(1));
+
+		assert_ok!(transfer(1, file_id, 2));
+		assert_eq!(nft_owner(file_id), Some(2));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn file_nfts_only_move_with_their_file() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+
+		// The NFT pallet refuses to move a file NFT on its own.
+		assert_noop!(Uniques::transfer(Origin::signed(1), 0, file_id, 2), pallet_uniques::Error::<Test>::Locked);
+		assert_eq!(nft_owner(file_id), Some(1));
+
+		// NFTs of other collections are not locked.
+		assert_ok!(Uniques::create(Origin::signed(1), 1, 1));
+		assert_ok!(Uniques::mint(Origin::signed(1), 1, file_id, 1));
+		assert_ok!(Uniques::transfer(Origin::signed(1), 1, file_id, 2));
+
+		assert_ok!(transfer(1, file_id, 2));
+		assert_eq!(nft_owner(file_id), Some(2));
+		assert_noop!(Uniques::transfer(Origin::signed(2), 0, file_id, 3), pallet_uniques::Error::<Test>::Locked);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn burned_file_nft_is_minted_again_for_the_next_owner() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+
+		// Holders can burn a file NFT, which leaves the file in place.
+		assert_ok!(Uniques::burn(Origin::signed(1), 0, file_id, None));
+		assert_eq!(nft_owner(file_id), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		assert_ok!(transfer(1, file_id, 2));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 2);
+		assert_eq!(nft_owner(file_id), Some(2));
+		assert_noop!(Uniques::transfer(Origin::signed(2), 0, file_id, 3), pallet_uniques::Error::<Test>::Locked);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// A file whose NFT is gone is still removed once its rent runs out.
+		assert_ok!(Uniques::burn(Origin::signed(2), 0, file_id, None));
+		System::set_block_number(12);
+		TemplateModule::on_idle(12, u64::MAX);
+		assert_eq!(TemplateModule::get_file_details(file_id), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn nft_collection_cannot_be_squatted() {
+	new_test_ext().execute_with(|| {
+		// The collection is created at genesis, and its id is refused to everyone else.
+		assert_eq!(<Uniques as Inspect<u64>>::collection_owner(&0), Some(TemplateModule::nft_account()));
+		assert_noop!(Uniques::create(Origin::signed(5), 0, 5), BadOrigin);
+		assert_ok!(Uniques::create(Origin::signed(5), 1, 5));
+
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_eq!(nft_owner(file_id), Some(1));
+	});
+}
+
+#[test]
+fn listed_file_is_bought_at_its_price() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_noop!(TemplateModule::list_file(Origin::signed(2), file_id, 500), Error::<Test>::InvalidSigner);
+		assert_noop!(TemplateModule::buy_file(Origin::signed(2), file_id, 500), Error::<Test>::NotListed);
+
+		assert_ok!(TemplateModule::list_file(Origin::signed(1), file_id, 500));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Listed(file_id, 1, 500)));
+		assert_noop!(TemplateModule::buy_file(Origin::signed(1), file_id, 500), Error::<Test>::CannotBuyOwnFile);
+		assert_noop!(TemplateModule::buy_file(Origin::signed(2), file_id, 400), Error::<Test>::PriceAboveLimit);
+
+		let seller_balance = Balances::free_balance(1);
+		assert_ok!(TemplateModule::buy_file(Origin::signed(2), file_id, 500));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Sold(file_id, 1, 2, 500)));
+		assert_eq!(Balances::free_balance(1), seller_balance + 500);
+		assert_eq!(Balances::free_balance(2), 10_000 - 500);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 2);
+		assert_eq!(nft_owner(file_id), Some(2));
+		assert_eq!(TemplateModule::listings(file_id), None);
+		assert_noop!(TemplateModule::buy_file(Origin::signed(3), file_id, 500), Error::<Test>::NotListed);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn listing_ends_when_file_changes_hands() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::list_file(Origin::signed(1), file_id, 500));
+		assert_ok!(TemplateModule::delist_file(Origin::signed(1), file_id));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Delisted(file_id)));
+		assert_noop!(TemplateModule::delist_file(Origin::signed(1), file_id), Error::<Test>::NotListed);
+
+		assert_ok!(TemplateModule::list_file(Origin::signed(1), file_id, 500));
+		assert_ok!(transfer(1, file_id, 2));
+		System::assert_has_event(Event::TemplateModule(crate::Event::Delisted(file_id)));
+		assert_eq!(TemplateModule::listings(file_id), None);
+	});
+}
+
+#[test]
+fn owner_accepts_offers() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::make_offer(Origin::signed(2), file_id, 100));
+		assert_ok!(TemplateModule::make_offer(Origin::signed(3), file_id, 200));
+		assert_eq!(Balances::reserved_balance(2), 100);
+
+		// Once the offers are full, a new offer has to beat the lowest one.
+		assert_noop!(TemplateModule::make_offer(Origin::signed(4), file_id, 100), Error::<Test>::OfferTooLow);
+		assert_ok!(TemplateModule::make_offer(Origin::signed(4), file_id, 150));
+		System::assert_has_event(Event::TemplateModule(crate::Event::OfferWithdrawn(file_id, 2)));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_noop!(TemplateModule::withdraw_offer(Origin::signed(2), file_id), Error::<Test>::OfferNotFound);
+
+		// A new offer replaces the bidder's previous one.
+		assert_ok!(TemplateModule::make_offer(Origin::signed(3), file_id, 300));
+		assert_eq!(Balances::reserved_balance(3), 300);
+		assert_eq!(TemplateModule::offers(file_id).into_inner(), vec![(4, 150), (3, 300)]);
+
+		assert_noop!(TemplateModule::accept_offer(Origin::signed(2), file_id, 4), Error::<Test>::InvalidSigner);
+		let seller_balance = Balances::free_balance(1);
+		assert_ok!(TemplateModule::accept_offer(Origin::signed(1), file_id, 4));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Sold(file_id, 1, 4, 150)));
+		assert_eq!(Balances::free_balance(1), seller_balance + 150);
+		assert_eq!(Balances::reserved_balance(4), 0);
+		assert_eq!(Balances::free_balance(4), 10_000 - 150);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 4);
+
+		// Other offers stay open for the new owner.
+		assert_eq!(TemplateModule::offers(file_id).into_inner(), vec![(3, 300)]);
+		assert_ok!(TemplateModule::withdraw_offer(Origin::signed(3), file_id));
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn auction_sells_file_to_highest_bid() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::list_file(Origin::signed(1), file_id, 500));
+		assert_noop!(TemplateModule::create_auction(Origin::signed(1), file_id, 100, 1), Error::<Test>::InvalidAuctionEnd);
+		assert_noop!(TemplateModule::create_auction(Origin::signed(1), file_id, 100, 12), Error::<Test>::AuctionTooLong);
+		assert_noop!(TemplateModule::create_auction(Origin::signed(2), file_id, 100, 5), Error::<Test>::InvalidSigner);
+		assert_ok!(TemplateModule::create_auction(Origin::signed(1), file_id, 100, 5));
+		System::assert_last_event(Event::TemplateModule(crate::Event::AuctionCreated(file_id, 1, 100, 5)));
+		assert_eq!(TemplateModule::listings(file_id), None);
+
+		// The file stays with the seller until the auction ends.
+		assert_noop!(TemplateModule::create_auction(Origin::signed(1), file_id, 100, 6), Error::<Test>::AuctionInProgress);
+		assert_noop!(TemplateModule::list_file(Origin::signed(1), file_id, 500), Error::<Test>::AuctionInProgress);
+		assert_noop!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2), Error::<Test>::AuctionInProgress);
+
+		assert_noop!(TemplateModule::bid(Origin::signed(1), file_id, 100), Error::<Test>::CannotBuyOwnFile);
+		assert_noop!(TemplateModule::bid(Origin::signed(2), file_id, 50), Error::<Test>::BidTooLow);
+		assert_ok!(TemplateModule::bid(Origin::signed(2), file_id, 100));
+		assert_eq!(Balances::reserved_balance(2), 100);
+		assert_noop!(TemplateModule::bid(Origin::signed(3), file_id, 100), Error::<Test>::BidTooLow);
+		assert_ok!(TemplateModule::bid(Origin::signed(3), file_id, 150));
+		System::assert_has_event(Event::TemplateModule(crate::Event::Outbid(file_id, 2, 100)));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_ok!(TemplateModule::bid(Origin::signed(3), file_id, 200));
+		assert_eq!(Balances::reserved_balance(3), 200);
+
+		let seller_balance = Balances::free_balance(1);
+		run_to_block(4);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 1);
+		run_to_block(5);
+		System::assert_has_event(Event::TemplateModule(crate::Event::Sold(file_id, 1, 3, 200)));
+		assert_eq!(Balances::free_balance(1), seller_balance + 200);
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(Balances::free_balance(3), 10_000 - 200);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 3);
+		assert_eq!(nft_owner(file_id), Some(3));
+		assert_eq!(TemplateModule::auctions(file_id), None);
+		assert_noop!(TemplateModule::bid(Origin::signed(2), file_id, 300), Error::<Test>::AuctionNotFound);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn auctions_end_without_sale() {
+	new_test_ext().execute_with(|| {
+		let first = upload(1, 1, FileType::Normal, 100, 100);
+		let second = upload(1, 2, FileType::Normal, 100, 100);
+
+		// Only auctions without bids can be cancelled.
+		assert_ok!(TemplateModule::create_auction(Origin::signed(1), first, 100, 3));
+		assert_ok!(TemplateModule::bid(Origin::signed(2), first, 100));
+		assert_noop!(TemplateModule::cancel_auction(Origin::signed(1), first), Error::<Test>::AuctionHasBids);
+		assert_ok!(TemplateModule::create_auction(Origin::signed(1), second, 100, 3));
+		assert_noop!(TemplateModule::cancel_auction(Origin::signed(2), second), Error::<Test>::InvalidSigner);
+		assert_ok!(TemplateModule::cancel_auction(Origin::signed(1), second));
+		System::assert_last_event(Event::TemplateModule(crate::Event::AuctionCancelled(second)));
+		assert_noop!(TemplateModule::cancel_auction(Origin::signed(1), second), Error::<Test>::AuctionNotFound);
+
+		// An auction without bids ends without a sale.
+		assert_ok!(TemplateModule::create_auction(Origin::signed(1), second, 100, 3));
+
+		run_to_block(3);
+		System::assert_has_event(Event::TemplateModule(crate::Event::AuctionCancelled(second)));
+		assert_eq!(TemplateModule::get_file_details(second).unwrap().owner, 1);
+		assert_eq!(TemplateModule::get_file_details(first).unwrap().owner, 2);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn offered_transfer_is_accepted() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_noop!(TemplateModule::transfer_file(Origin::signed(1), file_id, 1), Error::<Test>::InvalidOperation);
+		assert_noop!(TemplateModule::transfer_file(Origin::signed(2), file_id, 3), Error::<Test>::InvalidSigner);
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		System::assert_last_event(Event::TemplateModule(crate::Event::TransferOffered(file_id, 1, 2, 6)));
+
+		// The file stays with the owner until the recipient accepts it.
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 1);
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(3), file_id), Error::<Test>::TransferNotFound);
+		assert_ok!(TemplateModule::accept_transfer(Origin::signed(2), file_id));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Transfered(1, 2, file_id)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 2);
+		assert_eq!(TemplateModule::get_user_file_details(2).into_inner(), vec![file_id]);
+		assert_eq!(nft_owner(file_id), Some(2));
+		assert_eq!(TemplateModule::pending_transfers(file_id), None);
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::TransferNotFound);
+
+		// An offer is dropped once the file changes hands another way.
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(2), file_id, 3));
+		assert_ok!(TemplateModule::list_file(Origin::signed(2), file_id, 500));
+		assert_ok!(TemplateModule::buy_file(Origin::signed(4), file_id, 500));
+		assert_eq!(TemplateModule::pending_transfers(file_id), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn offered_transfer_is_rejected_or_expires() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		assert_noop!(TemplateModule::reject_transfer(Origin::signed(3), file_id), Error::<Test>::TransferNotFound);
+		assert_ok!(TemplateModule::reject_transfer(Origin::signed(2), file_id));
+		System::assert_last_event(Event::TemplateModule(crate::Event::TransferRejected(file_id, 2)));
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::TransferNotFound);
+
+		// The owner can take the offer back.
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		assert_ok!(TemplateModule::reject_transfer(Origin::signed(1), file_id));
+		System::assert_last_event(Event::TemplateModule(crate::Event::TransferRejected(file_id, 1)));
+
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, 2));
+		run_to_block(6);
+		assert!(TemplateModule::pending_transfers(file_id).is_some());
+		run_to_block(7);
+		System::assert_has_event(Event::TemplateModule(crate::Event::TransferOfferExpired(file_id)));
+		assert_noop!(TemplateModule::accept_transfer(Origin::signed(2), file_id), Error::<Test>::TransferNotFound);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 1);
+	});
+}
+
+#[test]
+fn replaced_transfer_offers_free_their_deadline() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		let other = upload(1, 2, FileType::Normal, 100, 100);
+
+		// Offers replaced in the same block keep a single entry in the block they expire in.
+		for recipient in 2..=5 {
+			assert_ok!(TemplateModule::transfer_file(Origin::signed(1), file_id, recipient));
+		}
+		assert_eq!(crate::TransferOfferDeadlines::<Test>::get(7).into_inner(), vec![file_id]);
+		assert_ok!(TemplateModule::transfer_file(Origin::signed(1), other, 2));
+
+		assert_ok!(TemplateModule::reject_transfer(Origin::signed(5), file_id));
+		assert_eq!(crate::TransferOfferDeadlines::<Test>::get(7).into_inner(), vec![other]);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+fn co_owners(owners: Vec<u64>) -> BoundedVec<u64, <Test as crate::Config>::MaxCoOwners> {
+	owners.try_into().unwrap()
+}
+
+#[test]
+fn co_owners_approve_changes() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::update_file(Origin::signed(1), file_id, 150, true));
+		System::assert_last_event(Event::TemplateModule(crate::Event::FileUpdated(file_id, 150, true)));
+
+		assert_noop!(
+			TemplateModule::set_co_owners(Origin::signed(2), file_id, co_owners(vec![1, 2, 3]), 2),
+			Error::<Test>::InvalidSigner
+		);
+		assert_noop!(
+			TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 0),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 4),
+			Error::<Test>::InvalidThreshold
+		);
+		assert_noop!(
+			TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 2]), 2),
+			Error::<Test>::DuplicateCoOwner
+		);
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 2));
+		let shared = TemplateModule::co_owner_account(&file_id);
+		System::assert_last_event(Event::TemplateModule(crate::Event::CoOwnersSet(file_id, shared, 2)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, shared);
+		assert_eq!(nft_owner(file_id), Some(shared));
+
+		// Co-owners no longer change the file on their own.
+		assert_noop!(TemplateModule::update_file(Origin::signed(1), file_id, 200, false), Error::<Test>::InvalidSigner);
+		assert_noop!(
+			TemplateModule::propose(Origin::signed(4), file_id, FileAction::Update(200, false)),
+			Error::<Test>::NotCoOwner
+		);
+		assert_ok!(TemplateModule::propose(Origin::signed(1), file_id, FileAction::Update(200, false)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Proposed(file_id, 0, 1)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().cost, 150);
+		assert_noop!(TemplateModule::approve(Origin::signed(1), file_id, 0), Error::<Test>::AlreadyApproved);
+		assert_ok!(TemplateModule::approve(Origin::signed(2), file_id, 0));
+		System::assert_last_event(Event::TemplateModule(crate::Event::ProposalExecuted(file_id, 0)));
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!((file.cost, file.allow_download), (200, false));
+		assert_noop!(TemplateModule::approve(Origin::signed(3), file_id, 0), Error::<Test>::ProposalNotFound);
+
+		// Only the proposer can cancel a proposal.
+		assert_ok!(TemplateModule::propose(Origin::signed(2), file_id, FileAction::Delist));
+		assert_noop!(TemplateModule::cancel_proposal(Origin::signed(3), file_id, 1), Error::<Test>::InvalidSigner);
+		assert_ok!(TemplateModule::cancel_proposal(Origin::signed(2), file_id, 1));
+		assert_eq!(TemplateModule::proposals(file_id, 1), None);
+
+		// Transferring the file ends the co-ownership.
+		assert_ok!(TemplateModule::propose(Origin::signed(3), file_id, FileAction::Transfer(3)));
+		assert_ok!(TemplateModule::propose(Origin::signed(1), file_id, FileAction::Delist));
+		assert_noop!(
+			TemplateModule::propose(Origin::signed(2), file_id, FileAction::Delist),
+			Error::<Test>::TooManyProposals
+		);
+		assert_eq!(TemplateModule::co_owners(file_id).unwrap().open_proposals, 2);
+		assert_ok!(TemplateModule::approve(Origin::signed(1), file_id, 2));
+		assert_eq!(TemplateModule::pending_transfers(file_id).map(|pending| pending.to), Some(3));
+		assert_ok!(TemplateModule::accept_transfer(Origin::signed(3), file_id));
+		System::assert_has_event(Event::TemplateModule(crate::Event::Transfered(shared, 3, file_id)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 3);
+		assert_eq!(nft_owner(file_id), Some(3));
+		assert_eq!(TemplateModule::co_owners(file_id), None);
+		assert_eq!(TemplateModule::proposals(file_id, 3), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn co_owners_split_sale_revenue() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 2));
+		assert_ok!(TemplateModule::propose(Origin::signed(1), file_id, FileAction::List(500)));
+		assert_ok!(TemplateModule::approve(Origin::signed(3), file_id, 0));
+		let shared = TemplateModule::co_owner_account(&file_id);
+		assert_eq!(TemplateModule::listings(file_id).map(|listing| listing.seller), Some(shared));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		let balances: Vec<u64> = (1..=3).map(Balances::free_balance).collect();
+		assert_ok!(TemplateModule::buy_file(Origin::signed(4), file_id, 500));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Sold(file_id, shared, 4, 500)));
+
+		// The first co-owner gets the remainder of the equal shares.
+		assert_eq!(Balances::free_balance(1), balances[0] + 168);
+		assert_eq!(Balances::free_balance(2), balances[1] + 166);
+		assert_eq!(Balances::free_balance(3), balances[2] + 166);
+		assert_eq!(Balances::free_balance(4), 10_000 - 500);
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 4);
+		assert_eq!(TemplateModule::co_owners(file_id), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn co_owners_manage_rent_access_and_versions() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Encrypted, 100, 100);
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 2));
+
+		// Any co-owner can pay the rent.
+		assert_noop!(TemplateModule::extend_storage(Origin::signed(4), file_id, 10), Error::<Test>::InvalidSigner);
+		assert_ok!(TemplateModule::extend_storage(Origin::signed(2), file_id, 10));
+		assert_eq!(Balances::free_balance(2), 10_000 - 1_000);
+		assert_eq!(TemplateModule::file_rent(file_id).unwrap().paid_through, 11);
+
+		// The proposer pays the upload fee of a new version.
+		UploadFeePerByte::set(1);
+		let action = FileAction::PublishVersion([2; 20], 200, content_root(1, 2, 200), 16);
+		assert_ok!(TemplateModule::propose(Origin::signed(3), file_id, action));
+		assert_ok!(TemplateModule::approve(Origin::signed(1), file_id, 0));
+		System::assert_has_event(Event::TemplateModule(crate::Event::VersionPublished(file_id, 1)));
+		assert_eq!(Balances::free_balance(3), 10_000 - 200);
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!((file.version, file.file_size), (1, 200));
+
+		assert_ok!(TemplateModule::propose(Origin::signed(1), file_id, FileAction::Rollback(0)));
+		assert_ok!(TemplateModule::approve(Origin::signed(2), file_id, 1));
+		System::assert_has_event(Event::TemplateModule(crate::Event::RolledBack(file_id, 0)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().version, 0);
+
+		assert_ok!(TemplateModule::propose(Origin::signed(2), file_id, FileAction::GrantAccess(4, envelope(b"key"))));
+		assert_ok!(TemplateModule::approve(Origin::signed(3), file_id, 2));
+		assert_eq!(TemplateModule::key_envelope(file_id, 4), Some(envelope(b"key")));
+		assert_ok!(TemplateModule::propose(Origin::signed(2), file_id, FileAction::RevokeAccess(4)));
+		assert_ok!(TemplateModule::approve(Origin::signed(1), file_id, 3));
+		assert_eq!(TemplateModule::key_envelope(file_id, 4), None);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn co_owners_accept_offers_and_auction() {
+	new_test_ext().execute_with(|| {
+		let offered = upload(1, 1, FileType::Normal, 100, 100);
+		let auctioned = upload(1, 2, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), offered, co_owners(vec![1, 2, 3]), 2));
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), auctioned, co_owners(vec![1, 2, 3]), 2));
+		let balances: Vec<u64> = (1..=3).map(Balances::free_balance).collect();
+
+		assert_ok!(TemplateModule::make_offer(Origin::signed(4), offered, 300));
+		assert_ok!(TemplateModule::propose(Origin::signed(1), offered, FileAction::AcceptOffer(4)));
+		assert_ok!(TemplateModule::approve(Origin::signed(2), offered, 0));
+		let shared = TemplateModule::co_owner_account(&offered);
+		System::assert_has_event(Event::TemplateModule(crate::Event::Sold(offered, shared, 4, 300)));
+		assert_eq!(TemplateModule::get_file_details(offered).unwrap().owner, 4);
+		assert_eq!(TemplateModule::co_owners(offered), None);
+		assert_eq!(Balances::reserved_balance(4), 0);
+
+		assert_ok!(TemplateModule::propose(Origin::signed(2), auctioned, FileAction::CreateAuction(100, 5)));
+		assert_ok!(TemplateModule::approve(Origin::signed(3), auctioned, 0));
+		let shared = TemplateModule::co_owner_account(&auctioned);
+		assert_eq!(TemplateModule::auctions(auctioned).map(|auction| auction.seller), Some(shared));
+		assert_ok!(TemplateModule::bid(Origin::signed(5), auctioned, 200));
+		run_to_block(5);
+		assert_eq!(TemplateModule::get_file_details(auctioned).unwrap().owner, 5);
+		assert_eq!(TemplateModule::co_owners(auctioned), None);
+
+		// Both sales are split among the co-owners.
+		assert_eq!(Balances::free_balance(1), balances[0] + 100 + 68);
+		assert_eq!(Balances::free_balance(2), balances[1] + 100 + 66);
+		assert_eq!(Balances::free_balance(3), balances[2] + 100 + 66);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn co_owners_are_replaced_by_proposal() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::set_co_owners(Origin::signed(1), file_id, co_owners(vec![1, 2, 3]), 2));
+		let shared = TemplateModule::co_owner_account(&file_id);
+		assert_ok!(TemplateModule::propose(Origin::signed(1), file_id, FileAction::Update(200, false)));
+
+		let action = FileAction::SetCoOwners(co_owners(vec![2, 4]), 1);
+		assert_ok!(TemplateModule::propose(Origin::signed(2), file_id, action));
+		assert_ok!(TemplateModule::approve(Origin::signed(3), file_id, 1));
+		System::assert_has_event(Event::TemplateModule(crate::Event::CoOwnersSet(file_id, shared, 1)));
+		let co_ownership = TemplateModule::co_owners(file_id).unwrap();
+		assert_eq!((co_ownership.owners.into_inner(), co_ownership.threshold), (vec![2, 4], 1));
+		assert_eq!((co_ownership.next_proposal_id, co_ownership.open_proposals), (2, 0));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, shared);
+
+		// Proposals of the previous co-owners are dropped, and the new ones act on their own.
+		assert_eq!(TemplateModule::proposals(file_id, 0), None);
+		assert_noop!(TemplateModule::approve(Origin::signed(2), file_id, 0), Error::<Test>::ProposalNotFound);
+		assert_noop!(
+			TemplateModule::propose(Origin::signed(1), file_id, FileAction::Delist),
+			Error::<Test>::NotCoOwner
+		);
+		assert_ok!(TemplateModule::propose(Origin::signed(4), file_id, FileAction::Update(300, true)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().cost, 300);
+
+		let action = FileAction::SetCoOwners(co_owners(vec![2, 4]), 3);
+		assert_noop!(TemplateModule::propose(Origin::signed(4), file_id, action), Error::<Test>::InvalidThreshold);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+fn upload_for(operator: u64, owner: u64, link: u8) -> DispatchResult {
+	TemplateModule::upload_file_for(
+		Origin::signed(operator),
+		owner,
+		[link; 20],
+		true,
+		FileType::Normal,
+		100,
+		100,
+		content_root(owner, link, 100),
+		16,
+		1,
+	)
+}
+
+#[test]
+fn operators_act_within_their_scope() {
+	new_test_ext().execute_with(|| {
+		let scope = OperatorScope { upload: true, update: true, transfer: false };
+		assert_noop!(TemplateModule::approve_operator(Origin::signed(1), 1, scope, None), Error::<Test>::InvalidOperation);
+		assert_ok!(TemplateModule::approve_operator(Origin::signed(1), 5, scope, Some(4)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::OperatorApproved(1, 5, scope, Some(4))));
+
+		assert_noop!(upload_for(6, 1, 1), Error::<Test>::NotOperator);
+		assert_ok!(upload_for(5, 1, 1));
+		let file_id = *TemplateModule::get_user_file_details(1).last().unwrap();
+		System::assert_has_event(Event::TemplateModule(crate::Event::Uploaded(1, file_id)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::OperatorActed(5, 1, file_id)));
+		assert!(TemplateModule::get_user_file_details(5).is_empty());
+
+		assert_ok!(TemplateModule::update_file_for(Origin::signed(5), 1, file_id, 300, false));
+		System::assert_last_event(Event::TemplateModule(crate::Event::OperatorActed(5, 1, file_id)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().cost, 300);
+		assert_noop!(
+			TemplateModule::transfer_file_for(Origin::signed(5), 1, file_id, 2),
+			Error::<Test>::NotOperator
+		);
+
+		// The approval ends after its last block.
+		run_to_block(5);
+		assert_noop!(
+			TemplateModule::update_file_for(Origin::signed(5), 1, file_id, 200, true),
+			Error::<Test>::OperatorExpired
+		);
+		assert_noop!(TemplateModule::approve_operator(Origin::signed(1), 5, scope, Some(4)), Error::<Test>::OperatorExpired);
+	});
+}
+
+#[test]
+fn operators_transfer_and_are_revoked() {
+	new_test_ext().execute_with(|| {
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		let scope = OperatorScope { transfer: true, ..Default::default() };
+		assert_ok!(TemplateModule::approve_operator(Origin::signed(1), 5, scope, None));
+		assert_noop!(upload_for(5, 1, 2), Error::<Test>::NotOperator);
+
+		assert_ok!(TemplateModule::transfer_file_for(Origin::signed(5), 1, file_id, 2));
+		System::assert_last_event(Event::TemplateModule(crate::Event::OperatorActed(5, 1, file_id)));
+		assert_ok!(TemplateModule::accept_transfer(Origin::signed(2), file_id));
+		System::assert_has_event(Event::TemplateModule(crate::Event::Transfered(1, 2, file_id)));
+		assert_eq!(TemplateModule::get_file_details(file_id).unwrap().owner, 2);
+		assert_eq!(nft_owner(file_id), Some(2));
+
+		// An operator of the previous owner cannot move the file any more.
+		assert_noop!(
+			TemplateModule::transfer_file_for(Origin::signed(5), 1, file_id, 3),
+			Error::<Test>::InvalidSigner
+		);
+		assert_noop!(
+			TemplateModule::transfer_file_for(Origin::signed(5), 2, file_id, 3),
+			Error::<Test>::NotOperator
+		);
+
+		assert_ok!(TemplateModule::revoke_operator(Origin::signed(1), 5));
+		System::assert_last_event(Event::TemplateModule(crate::Event::OperatorRevoked(1, 5)));
+		assert_noop!(TemplateModule::revoke_operator(Origin::signed(1), 5), Error::<Test>::NotOperator);
+		assert_eq!(TemplateModule::operators(1, 5), None);
+	});
+}
+
+fn file_upload(owner: u64, link: u8, file_size: u64) -> FileUpload {
+	FileUpload {
+		file_link: [link; 20],
+		allow_download: true,
+		file_type: FileType::Normal,
+		cost: 100,
+		file_size,
+		content_root: content_root(owner, link, file_size),
+		chunk_size: 16,
+		replication_factor: 1,
+	}
+}
+
+fn batch<I>(items: Vec<I>) -> BoundedVec<I, <Test as crate::Config>::MaxBatchSize> {
+	items.try_into().unwrap()
+}
+
+#[test]
+fn upload_files_uploads_every_file_or_none() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::upload_files(
+			Origin::signed(1),
+			batch(vec![file_upload(1, 1, 100), file_upload(1, 2, 100)])
+		));
+		let uploaded = TemplateModule::get_user_file_details(1);
+		assert_eq!(uploaded.len(), 2);
+		for file_id in uploaded.iter() {
+			System::assert_has_event(Event::TemplateModule(crate::Event::Uploaded(1, *file_id)));
+		}
+		assert_eq!(TemplateModule::all_files_count(), 2);
+
+		// The second file of the batch already exists, so the first is not uploaded either.
+		assert_noop!(
+			TemplateModule::upload_files(Origin::signed(1), batch(vec![file_upload(1, 3, 100), file_upload(1, 1, 100)])),
+			Error::<Test>::FileAlreadyExists
+		);
+		assert_eq!(TemplateModule::get_user_file_details(1).len(), 2);
+	});
+}
+
+#[test]
+fn download_files_buys_every_file_or_none() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let first = upload(1, 1, FileType::Normal, 100, 250);
+		let second = upload(1, 2, FileType::Privileged, 150, 250);
+
+		let info = TemplateModule::download_files(Origin::signed(2), batch(vec![(first, None, LicenseTier::Personal), (second, None, LicenseTier::Personal)])).unwrap();
+		assert_eq!(
+			info.actual_weight,
+			Some(<() as WeightInfo>::download_file_normal(0) + <() as WeightInfo>::download_file_privileged(0))
+		);
+		System::assert_has_event(Event::TemplateModule(crate::Event::Downloaded(2, first, 0, 100)));
+		System::assert_has_event(Event::TemplateModule(crate::Event::Downloaded(2, second, 0, 150)));
+		assert_eq!(Balances::free_balance(2), 10_000 - 250);
+		assert_eq!(Balances::free_balance(ACCOUNTANT), 10 + 250);
+		assert_eq!(TemplateModule::all_downloads_count(), 2);
+
+		// The same file twice fails the whole batch.
+		assert_noop!(
+			TemplateModule::download_files(Origin::signed(3), batch(vec![(first, None, LicenseTier::Personal), (first, None, LicenseTier::Personal)])),
+			Error::<Test>::AlreadyDownloaded
+		);
+		assert_eq!(Balances::free_balance(3), 10_000);
+	});
+}
+
+#[test]
+fn licenses_let_buyers_fetch_files_again() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 250);
+
+		assert_noop!(TemplateModule::fetch_file(Origin::signed(2), file_id, None), Error::<Test>::LicenseNotFound);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_eq!(
+			TemplateModule::licenses(file_id, (2, 0)),
+			Some(License {
+				version: 0,
+				tier: LicenseTier::Personal,
+				kind: LicenseKind::AllRightsReserved,
+				terms_hash: None,
+				fetches: Some(2),
+			})
+		);
+		assert_noop!(TemplateModule::fetch_file(Origin::signed(3), file_id, None), Error::<Test>::LicenseNotFound);
+
+		// Fetching again is free but uses up the license.
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, None));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Fetched(file_id, 2, 0, Some(1))));
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, None));
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)).map(|license| license.fetches), Some(Some(0)));
+		assert_noop!(TemplateModule::fetch_file(Origin::signed(2), file_id, None), Error::<Test>::NoFetchesLeft);
+		assert_eq!(Balances::free_balance(2), 10_000 - 100);
+
+		// An exhausted license is renewed by paying again.
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_eq!(Balances::free_balance(2), 10_000 - 200);
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)).map(|license| license.fetches), Some(Some(2)));
+		assert_eq!(TemplateModule::get_download_details(file_id, 0).into_inner(), vec![2]);
+		assert_eq!(TemplateModule::all_downloads_count(), 1);
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, None));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn licenses_are_kept_per_version() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 100);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_ok!(publish(1, file_id, 2, 100));
+
+		// Buying the new version leaves the license of the old one alone.
+		assert_noop!(TemplateModule::fetch_file(Origin::signed(2), file_id, None), Error::<Test>::LicenseNotFound);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, Some(0)));
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, Some(1)));
+		System::assert_last_event(Event::TemplateModule(crate::Event::Fetched(file_id, 2, 1, Some(1))));
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)).map(|license| license.fetches), Some(Some(1)));
+		assert_eq!(TemplateModule::licenses(file_id, (2, 1)).map(|license| license.version), Some(1));
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn unlimited_licenses_never_run_out() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		FetchesPerLicense::set(None);
+		let file_id = upload(1, 1, FileType::Normal, 100, 250);
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		for _ in 0..5 {
+			assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, None));
+		}
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)).map(|license| license.fetches), Some(None));
+	});
+}
+
+#[test]
+fn commercial_use_is_bought_under_the_terms_set_by_the_owner() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 250);
+		let terms_hash = H256::repeat_byte(7);
+
+		// Files are sold for personal use only until the owner sets a commercial cost.
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Commercial),
+			Error::<Test>::CommercialUseNotOffered
+		);
+		assert_noop!(
+			TemplateModule::set_license(
+				Origin::signed(1),
+				file_id,
+				LicenseTerms { kind: LicenseKind::Custom, terms_hash: None, commercial_cost: Some(400) }
+			),
+			Error::<Test>::InvalidLicense
+		);
+		assert_noop!(
+			TemplateModule::set_license(Origin::signed(2), file_id, LicenseTerms::default()),
+			Error::<Test>::InvalidSigner
+		);
+		let license = LicenseTerms { kind: LicenseKind::Custom, terms_hash: Some(terms_hash), commercial_cost: Some(400) };
+		assert_ok!(TemplateModule::set_license(Origin::signed(1), file_id, license));
+		System::assert_last_event(Event::TemplateModule(crate::Event::LicenseSet(file_id, license)));
+
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Commercial));
+		assert_ok!(TemplateModule::download_file(Origin::signed(3), file_id, None, LicenseTier::Personal));
+		assert_eq!(Balances::free_balance(2), 10_000 - 400);
+		assert_eq!(Balances::free_balance(3), 10_000 - 100);
+		assert_eq!(TemplateModule::licenses(file_id, (2, 0)).map(|license| license.tier), Some(LicenseTier::Commercial));
+		assert_eq!(TemplateModule::licenses(file_id, (3, 0)).map(|license| license.tier), Some(LicenseTier::Personal));
+
+		// Licenses already bought keep the terms they were bought under.
+		assert_ok!(TemplateModule::set_license(Origin::signed(1), file_id, LicenseTerms::default()));
+		let bought = TemplateModule::licenses(file_id, (2, 0)).unwrap();
+		assert_eq!((bought.kind, bought.terms_hash), (LicenseKind::Custom, Some(terms_hash)));
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(4), file_id, None, LicenseTier::Commercial),
+			Error::<Test>::CommercialUseNotOffered
+		);
+	});
+}
+
+#[test]
+fn personal_licenses_upgrade_to_commercial_use() {
+	new_test_ext().execute_with(|| {
+		set_accountant();
+		let file_id = upload(1, 1, FileType::Normal, 100, 250);
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal));
+		assert_ok!(TemplateModule::fetch_file(Origin::signed(2), file_id, None));
+
+		// Upgrades need commercial terms like any other commercial purchase.
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Commercial),
+			Error::<Test>::CommercialUseNotOffered
+		);
+		let terms_hash = H256::repeat_byte(7);
+		let license = LicenseTerms { kind: LicenseKind::Custom, terms_hash: Some(terms_hash), commercial_cost: Some(400) };
+		assert_ok!(TemplateModule::set_license(Origin::signed(1), file_id, license));
+
+		// The holder only pays the difference and keeps the fetches left on the license.
+		assert_ok!(TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Commercial));
+		System::assert_last_event(Event::TemplateModule(crate::Event::LicenseUpgraded(file_id, 2, 0, 300)));
+		assert_eq!(Balances::free_balance(2), 10_000 - 400);
+		let upgraded = TemplateModule::licenses(file_id, (2, 0)).unwrap();
+		assert_eq!(upgraded.tier, LicenseTier::Commercial);
+		assert_eq!((upgraded.kind, upgraded.terms_hash), (LicenseKind::Custom, Some(terms_hash)));
+		assert_eq!(upgraded.fetches, Some(1));
+		assert_eq!(TemplateModule::all_downloads_count(), 1);
+
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Commercial),
+			Error::<Test>::AlreadyDownloaded
+		);
+		assert_noop!(
+			TemplateModule::download_file(Origin::signed(2), file_id, None, LicenseTier::Personal),
+			Error::<Test>::AlreadyDownloaded
+		);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+	});
+}
+
+/// Stores a file as the first release did, returning the file id.
+fn put_legacy_file(owner: u64) -> H256 {
+	let file_id = H256::repeat_byte(1);
+	let old = migrations::v1::OldFile {
+		file_link: [1; 20],
+		allow_download: true,
+		file_type: FileType::Normal,
+		cost: 100,
+		file_size: 100,
+		owner,
+	};
+	frame_support::storage::unhashed::put(&Files::<Test>::hashed_key_for(file_id), &old);
+	FilesPerUser::<Test>::insert(owner, BoundedVec::try_from(vec![file_id]).unwrap());
+	AllFilesCount::<Test>::put(1);
+	StorageVersion::new(0).put::<TemplateModule>();
+	file_id
+}
+
+#[test]
+fn first_release_storage_is_migrated() {
+	new_test_ext().execute_with(|| {
+		// Files and downloads as the first release stored them.
+		let file_id = put_legacy_file(1);
+		let mut downloads_key = frame_support::storage::storage_prefix(b"TemplateModule", b"FileDownloads").to_vec();
+		downloads_key.extend(Twox64Concat::hash(&file_id.encode()));
+		frame_support::storage::unhashed::put(&downloads_key, &vec![3u64, 2]);
+		AllDownloadsCount::<Test>::put(2);
+
+		migrations::v1::migrate::<Test, CollectionDeposit, ItemDeposit>();
+		let file = TemplateModule::get_file_details(file_id).unwrap();
+		assert_eq!((file.owner, file.file_size, file.version, file.replication_factor), (1, 100, 0, 0));
+		assert_eq!(TemplateModule::get_download_details(file_id, 0).into_inner(), vec![2, 3]);
+		assert_eq!(TemplateModule::licenses(file_id, (3, 0)).map(|license| license.version), Some(0));
+		assert_eq!(TemplateModule::bytes_used(1), 100);
+		assert_eq!(nft_owner(file_id), Some(1));
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+		// Migrated chains are left alone.
+		assert_eq!(migrations::v1::migrate::<Test, CollectionDeposit, ItemDeposit>(), 0);
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn migration_pays_the_nft_deposits() {
 	new_test_ext().execute_with(|| {
-		// Dispatch a signed extrinsic.
-		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		let file_id = put_legacy_file(1);
+		ItemDeposit::set(30);
+		let nft_account = TemplateModule::nft_account();
+		assert_eq!(Balances::free_balance(nft_account), 0);
+
+		migrations::v1::migrate::<Test, CollectionDeposit, ItemDeposit>();
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(nft_owner(file_id), Some(1));
+		assert_eq!(Balances::reserved_balance(nft_account), 30);
+		assert_eq!(TemplateModule::do_try_state(), Ok(()));
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn failed_migration_is_rolled_back() {
 	new_test_ext().execute_with(|| {
-		// Ensure the expected error is thrown when no value is present.
-		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
+		let file_id = put_legacy_file(1);
+		// The NFT id of the legacy file is already taken.
+		assert_ok!(TemplateModule::mint_nft(&file_id, &2));
+
+		migrations::v1::migrate::<Test, CollectionDeposit, ItemDeposit>();
+		assert_eq!(TemplateModule::on_chain_storage_version(), 0);
+		let stored = frame_support::storage::unhashed::get::<migrations::v1::OldFile<u64>>(
+			&Files::<Test>::hashed_key_for(file_id),
+		);
+		assert_eq!(stored.map(|old| old.owner), Some(1));
+		assert_eq!(TemplateModule::bytes_used(1), 0);
 	});
 }
@@ -0,0 +1,1393 @@
+//! Weights for pallet_mydropbox
+//!
+//! PLACEHOLDERS: these values are hand-written estimates, not benchmark results. They only follow
+//! the layout of the benchmark CLI output, and the `Storage` comments list the storage each call
+//! is expected to touch. Before this runtime is used anywhere fees matter, replace the file with
+//! the output of the `pallet_mydropbox` benchmarks run on reference hardware, e.g.
+//!
+//! ```text
+//! ./target/release/node-dropbox benchmark pallet --chain=dev --execution=wasm \
+//!     --wasm-execution=compiled --pallet=pallet_mydropbox --extrinsic='*' --steps=50 \
+//!     --repeat=20 --output=pallets/mydropbox/src/weights.rs
+//! ```
+//!
+//! Counts of map entries follow the runtime's bounds: `collect_expired` removes files with
+//! `MaxVersions` = 16 older versions, each downloaded by `MaxFilesUploaded` = 100 license holders
+//! and hosted by `MaxProvidersPerFile` = 10 providers.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_mydropbox.
+pub trait WeightInfo {
+	fn add_accountant() -> Weight;
+	fn upload_file(f: u32, ) -> Weight;
+	fn download_file_normal(d: u32, ) -> Weight;
+	fn download_file_normal_oversized(d: u32, ) -> Weight;
+	fn download_file_privileged(d: u32, ) -> Weight;
+	fn register_provider() -> Weight;
+	fn update_provider() -> Weight;
+	fn deregister_provider(f: u32, ) -> Weight;
+	fn withdraw_stake() -> Weight;
+	fn assign_provider() -> Weight;
+	fn unassign_provider() -> Weight;
+	fn on_initialize(e: u32, c: u32, ) -> Weight;
+	fn respond_to_challenge(c: u32, ) -> Weight;
+	fn report_unavailable(f: u32, ) -> Weight;
+	fn fund_file() -> Weight;
+	fn replicate() -> Weight;
+	fn fulfill_replication() -> Weight;
+	fn extend_storage() -> Weight;
+	fn collect_expired(f: u32, ) -> Weight;
+	fn set_plan() -> Weight;
+	fn buy_plan() -> Weight;
+	fn publish_version() -> Weight;
+	fn rollback_to_version() -> Weight;
+	fn grant_access() -> Weight;
+	fn revoke_access() -> Weight;
+	fn download_file_encrypted(d: u32, ) -> Weight;
+	fn expire_key_requests(r: u32, ) -> Weight;
+	fn set_delivery_key() -> Weight;
+	fn fulfill_key_request() -> Weight;
+	fn list_file() -> Weight;
+	fn delist_file() -> Weight;
+	fn buy_file(f: u32, ) -> Weight;
+	fn make_offer() -> Weight;
+	fn withdraw_offer() -> Weight;
+	fn accept_offer(f: u32, ) -> Weight;
+	fn create_auction() -> Weight;
+	fn bid() -> Weight;
+	fn cancel_auction() -> Weight;
+	fn settle_auctions(a: u32, ) -> Weight;
+	fn transfer_file() -> Weight;
+	fn accept_transfer(f: u32, ) -> Weight;
+	fn reject_transfer() -> Weight;
+	fn expire_transfer_offers(t: u32, ) -> Weight;
+	fn update_file() -> Weight;
+	fn set_co_owners(f: u32, ) -> Weight;
+	fn propose() -> Weight;
+	fn approve() -> Weight;
+	fn cancel_proposal() -> Weight;
+	fn approve_operator() -> Weight;
+	fn revoke_operator() -> Weight;
+	fn upload_file_for(f: u32, ) -> Weight;
+	fn update_file_for() -> Weight;
+	fn transfer_file_for() -> Weight;
+	fn fetch_file() -> Weight;
+	fn set_license() -> Weight;
+	fn set_reporter() -> Weight;
+	fn upgrade_license(d: u32, ) -> Weight;
+}
+
+/// Placeholder weights for pallet_mydropbox, pending benchmarks on recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: TemplateModule Accountant (r:1 w:1)
+	fn add_accountant() -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:0 w:1)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques CollectionMaxSupply (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn upload_file(f: u32, ) -> Weight {
+		(101_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().writes(14 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_normal(d: u32, ) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_normal_oversized(d: u32, ) -> Weight {
+		(56_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_privileged(d: u32, ) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn register_provider() -> Weight {
+		(32_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	fn update_provider() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	fn deregister_provider(f: u32, ) -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add((9_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_stake() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Providers (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:0)
+	// Storage: TemplateModule ProviderContents (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	fn assign_provider() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	fn unassign_provider() -> Weight {
+		(34_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: TemplateModule ChallengeDeadlines (r:2 w:2)
+	// Storage: TemplateModule Challenges (r:2 w:2)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: TemplateModule ProviderContents (r:2 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	fn on_initialize(e: u32, c: u32, ) -> Weight {
+		(6_000_000 as Weight)
+			.saturating_add((28_000_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add((21_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+	}
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn respond_to_challenge(c: u32, ) -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule AvailabilityReporters (r:1 w:0)
+	// Storage: TemplateModule NextAvailabilityReport (r:0 w:1)
+	// Storage: TemplateModule Files (r:1 w:0)
+	fn report_unavailable(f: u32, ) -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add((5_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	fn fund_file() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: TemplateModule Providers (r:16 w:0)
+	fn replicate() -> Weight {
+		(95_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(22 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn fulfill_replication() -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule CoOwners (r:1 w:0)
+	fn extend_storage() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule RentCursor (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FileDownloads (r:18 w:17)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule KeyEnvelopes (r:257 w:256)
+	// Storage: TemplateModule KeyEnvelopeCount (r:0 w:1)
+	// Storage: TemplateModule Licenses (r:1701 w:1700)
+	// Storage: TemplateModule Listings (r:0 w:1)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:17 w:16)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule KeyRequests (r:101 w:100)
+	// Storage: System Account (r:134 w:134)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	// Storage: TemplateModule Contents (r:17 w:17)
+	// Storage: TemplateModule ContentProviders (r:17 w:17)
+	// Storage: TemplateModule ProviderContents (r:170 w:170)
+	// Storage: TemplateModule Providers (r:170 w:170)
+	// Storage: TemplateModule Challenges (r:170 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:17 w:17)
+	// Storage: TemplateModule StorageFunds (r:17 w:17)
+	// Storage: TemplateModule Accountant (r:17 w:0)
+	fn collect_expired(f: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((3_901_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2834 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2647 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: TemplateModule QuotaPlans (r:0 w:1)
+	fn set_plan() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule QuotaPlans (r:1 w:0)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:1)
+	fn buy_plan() -> Weight {
+		(43_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule Contents (r:2 w:2)
+	// Storage: System Account (r:102 w:102)
+	// Storage: TemplateModule StorageFunds (r:2 w:2)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule Licenses (r:0 w:100)
+	// Storage: TemplateModule KeyRequests (r:100 w:100)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn publish_version() -> Weight {
+		(1_458_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(215 as Weight))
+			.saturating_add(T::DbWeight::get().writes(313 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	fn rollback_to_version() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:0 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn grant_access() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:1 w:1)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn revoke_access() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:1 w:0)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule KeyRequestDeadlines (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_encrypted(d: u32, ) -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: TemplateModule KeyRequestDeadlines (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	fn expire_key_requests(r: u32, ) -> Weight {
+		(3_000_000 as Weight)
+			.saturating_add((31_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: TemplateModule DeliveryKeys (r:0 w:1)
+	fn set_delivery_key() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule DeliveryKeys (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn fulfill_key_request() -> Weight {
+		(88_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Listings (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	fn list_file() -> Weight {
+		(22_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	fn delist_file() -> Weight {
+		(23_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn buy_file(f: u32, ) -> Weight {
+		(86_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(16 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn make_offer() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_offer() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn accept_offer(f: u32, ) -> Weight {
+		(88_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(16 as Weight))
+			.saturating_add(T::DbWeight::get().writes(17 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	fn create_auction() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn bid() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	fn cancel_auction() -> Weight {
+		(29_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn settle_auctions(a: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((97_000_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((15 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((15 as Weight).saturating_mul(a as Weight)))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule TransferOfferDeadlines (r:2 w:2)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn transfer_file() -> Weight {
+		(27_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn accept_transfer(f: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(13 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	// Storage: TemplateModule TransferOfferDeadlines (r:1 w:1)
+	fn reject_transfer() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule TransferOfferDeadlines (r:1 w:1)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn expire_transfer_offers(t: u32, ) -> Weight {
+		(3_000_000 as Weight)
+			.saturating_add((9_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(t as Weight)))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn update_file() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn set_co_owners(f: u32, ) -> Weight {
+		(66_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(12 as Weight))
+			.saturating_add(T::DbWeight::get().writes(12 as Weight))
+	}
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:0 w:1)
+	fn propose() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:1)
+	fn approve() -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Proposals (r:1 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	fn cancel_proposal() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:0 w:1)
+	fn approve_operator() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:1)
+	fn revoke_operator() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:0 w:1)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques CollectionMaxSupply (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn upload_file_for(f: u32, ) -> Weight {
+		(106_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(14 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn update_file_for() -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule TransferOfferDeadlines (r:2 w:2)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn transfer_file_for() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:0)
+	fn fetch_file() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn set_license() -> Weight {
+		(22_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule AvailabilityReporters (r:0 w:1)
+	fn set_reporter() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:0)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn upgrade_license(d: u32, ) -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add((40_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
+
+// For backwards compatibility and tests, using the same placeholder values.
+impl WeightInfo for () {
+	// Storage: TemplateModule Accountant (r:1 w:1)
+	fn add_accountant() -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:0 w:1)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques CollectionMaxSupply (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn upload_file(f: u32, ) -> Weight {
+		(101_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(14 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_normal(d: u32, ) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_normal_oversized(d: u32, ) -> Weight {
+		(56_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_privileged(d: u32, ) -> Weight {
+		(55_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn register_provider() -> Weight {
+		(32_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	fn update_provider() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	fn deregister_provider(f: u32, ) -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add((9_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_stake() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Providers (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:0)
+	// Storage: TemplateModule ProviderContents (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	fn assign_provider() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	fn unassign_provider() -> Weight {
+		(34_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: TemplateModule ChallengeDeadlines (r:2 w:2)
+	// Storage: TemplateModule Challenges (r:2 w:2)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: TemplateModule ProviderContents (r:2 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	fn on_initialize(e: u32, c: u32, ) -> Weight {
+		(6_000_000 as Weight)
+			.saturating_add((28_000_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add((21_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+	}
+	// Storage: TemplateModule Challenges (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn respond_to_challenge(c: u32, ) -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule AvailabilityReporters (r:1 w:0)
+	// Storage: TemplateModule NextAvailabilityReport (r:0 w:1)
+	// Storage: TemplateModule Files (r:1 w:0)
+	fn report_unavailable(f: u32, ) -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add((5_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	fn fund_file() -> Weight {
+		(42_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ContentProviders (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: TemplateModule Providers (r:16 w:0)
+	fn replicate() -> Weight {
+		(95_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(22 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Contents (r:1 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule Providers (r:1 w:1)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ProviderContents (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn fulfill_replication() -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule CoOwners (r:1 w:0)
+	fn extend_storage() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule RentCursor (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FileDownloads (r:18 w:17)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule KeyEnvelopes (r:257 w:256)
+	// Storage: TemplateModule KeyEnvelopeCount (r:0 w:1)
+	// Storage: TemplateModule Licenses (r:1701 w:1700)
+	// Storage: TemplateModule Listings (r:0 w:1)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:17 w:16)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule KeyRequests (r:101 w:100)
+	// Storage: System Account (r:134 w:134)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	// Storage: TemplateModule Contents (r:17 w:17)
+	// Storage: TemplateModule ContentProviders (r:17 w:17)
+	// Storage: TemplateModule ProviderContents (r:170 w:170)
+	// Storage: TemplateModule Providers (r:170 w:170)
+	// Storage: TemplateModule Challenges (r:170 w:0)
+	// Storage: TemplateModule ReplicationOrders (r:17 w:17)
+	// Storage: TemplateModule StorageFunds (r:17 w:17)
+	// Storage: TemplateModule Accountant (r:17 w:0)
+	fn collect_expired(f: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((3_901_000_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2834 as Weight).saturating_mul(f as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2647 as Weight).saturating_mul(f as Weight)))
+	}
+	// Storage: TemplateModule QuotaPlans (r:0 w:1)
+	fn set_plan() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule QuotaPlans (r:1 w:0)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:1)
+	fn buy_plan() -> Weight {
+		(43_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule Contents (r:2 w:2)
+	// Storage: System Account (r:102 w:102)
+	// Storage: TemplateModule StorageFunds (r:2 w:2)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule Licenses (r:0 w:100)
+	// Storage: TemplateModule KeyRequests (r:100 w:100)
+	// Storage: TemplateModule ContentProviders (r:1 w:1)
+	// Storage: TemplateModule ReplicationOrders (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn publish_version() -> Weight {
+		(1_458_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(215 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(313 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:1)
+	fn rollback_to_version() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:0 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn grant_access() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:1 w:1)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn revoke_access() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:1 w:0)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule KeyRequestDeadlines (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:0 w:1)
+	fn download_file_encrypted(d: u32, ) -> Weight {
+		(61_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: TemplateModule KeyRequestDeadlines (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule FileDownloads (r:1 w:1)
+	// Storage: TemplateModule AllDownloadsCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	fn expire_key_requests(r: u32, ) -> Weight {
+		(3_000_000 as Weight)
+			.saturating_add((31_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((5 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: TemplateModule DeliveryKeys (r:0 w:1)
+	fn set_delivery_key() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule KeyRequests (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule DeliveryKeys (r:1 w:0)
+	// Storage: TemplateModule KeyEnvelopes (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule KeyEnvelopeCount (r:1 w:1)
+	fn fulfill_key_request() -> Weight {
+		(88_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Listings (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	fn list_file() -> Weight {
+		(22_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	fn delist_file() -> Weight {
+		(23_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn buy_file(f: u32, ) -> Weight {
+		(86_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(16 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn make_offer() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_offer() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule Offers (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn accept_offer(f: u32, ) -> Weight {
+		(88_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(16 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(17 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	fn create_auction() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn bid() -> Weight {
+		(52_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	fn cancel_auction() -> Weight {
+		(29_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule AuctionEnds (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn settle_auctions(a: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((97_000_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((15 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((15 as Weight).saturating_mul(a as Weight)))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule TransferOfferDeadlines (r:2 w:2)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn transfer_file() -> Weight {
+		(27_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn accept_transfer(f: u32, ) -> Weight {
+		(62_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(13 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	// Storage: TemplateModule TransferOfferDeadlines (r:1 w:1)
+	fn reject_transfer() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule TransferOfferDeadlines (r:1 w:1)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn expire_transfer_offers(t: u32, ) -> Weight {
+		(3_000_000 as Weight)
+			.saturating_add((9_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(t as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(t as Weight)))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn update_file() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:2 w:2)
+	// Storage: TemplateModule BytesUsed (r:2 w:2)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule FileVersions (r:1 w:0)
+	// Storage: Uniques Class (r:1 w:0)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques Account (r:0 w:2)
+	// Storage: Uniques ItemPriceOf (r:0 w:1)
+	// Storage: TemplateModule Listings (r:1 w:1)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule PendingTransfers (r:0 w:1)
+	// Storage: TemplateModule CoOwners (r:0 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:0)
+	fn set_co_owners(f: u32, ) -> Weight {
+		(66_000_000 as Weight)
+			.saturating_add((110_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(12 as Weight))
+	}
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:0 w:1)
+	fn propose() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	// Storage: TemplateModule Proposals (r:1 w:1)
+	fn approve() -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Proposals (r:1 w:1)
+	// Storage: TemplateModule CoOwners (r:1 w:1)
+	fn cancel_proposal() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:0 w:1)
+	fn approve_operator() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:1)
+	fn revoke_operator() -> Weight {
+		(19_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule BytesUsed (r:1 w:1)
+	// Storage: TemplateModule ExtraQuota (r:1 w:0)
+	// Storage: TemplateModule AllFilesCount (r:1 w:1)
+	// Storage: TemplateModule FilesPerUser (r:1 w:1)
+	// Storage: TemplateModule Contents (r:1 w:1)
+	// Storage: System Account (r:3 w:3)
+	// Storage: TemplateModule StorageFunds (r:1 w:1)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: TemplateModule FileRent (r:0 w:1)
+	// Storage: Uniques Class (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:1)
+	// Storage: Uniques CollectionMaxSupply (r:1 w:0)
+	// Storage: Uniques Account (r:0 w:1)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	fn upload_file_for(f: u32, ) -> Weight {
+		(106_000_000 as Weight)
+			.saturating_add((60_000 as Weight).saturating_mul(f as Weight))
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(14 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn update_file_for() -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Operators (r:1 w:0)
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule Auctions (r:1 w:0)
+	// Storage: TemplateModule TransferOfferDeadlines (r:2 w:2)
+	// Storage: TemplateModule PendingTransfers (r:1 w:1)
+	fn transfer_file_for() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:0)
+	fn fetch_file() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:1)
+	// Storage: Uniques Asset (r:1 w:0)
+	fn set_license() -> Weight {
+		(22_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule AvailabilityReporters (r:0 w:1)
+	fn set_reporter() -> Weight {
+		(14_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: TemplateModule Files (r:1 w:0)
+	// Storage: TemplateModule FileRent (r:1 w:0)
+	// Storage: TemplateModule FileDownloads (r:1 w:0)
+	// Storage: TemplateModule Licenses (r:1 w:1)
+	// Storage: TemplateModule KeyRequests (r:1 w:0)
+	// Storage: TemplateModule Accountant (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn upgrade_license(d: u32, ) -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add((40_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+}
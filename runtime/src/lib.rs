@@ -15,7 +15,7 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -28,15 +28,17 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness,
+		StorageInfo,
 	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::{EnsureRoot, EnsureSigned};
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
@@ -102,10 +104,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -143,6 +145,11 @@ parameter_types! {
 	pub BlockLength: frame_system::limits::BlockLength = frame_system::limits::BlockLength
 		::max_with_normal_ratio(5 * 1024 * 1024, NORMAL_DISPATCH_RATIO);
 	pub const SS58Prefix: u8 = 42;
+	pub const ChallengeSlash: Perbill = Perbill::from_percent(10);
+	pub const AvailabilityGateway: &'static str = "http://localhost:8080/files/";
+	pub const DropboxPalletId: PalletId = PalletId(*b"py/dropb");
+	pub const DuplicateUploadFee: Perbill = Perbill::from_percent(10);
+	pub const FetchesPerLicense: Option<u32> = None;
 	// pub const Dave: AccountId = hex_literal::hex!["d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"].into();
 	
 }
@@ -265,6 +272,34 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+impl pallet_uniques::Config for Runtime {
+	type Event = Event;
+	type CollectionId = u32;
+	type ItemId = Hash;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type CreateOrigin = pallet_mydropbox::EnsureNotNftCollection<Runtime, AsEnsureOriginWithArg<EnsureSigned<AccountId>>>;
+	// File NFTs only move along with their file.
+	type Locker = TemplateModule;
+	type CollectionDeposit = ConstU128<1_000_000>;
+	type ItemDeposit = ConstU128<1_000>;
+	type MetadataDepositBase = ConstU128<1_000>;
+	type AttributeDepositBase = ConstU128<1_000>;
+	type DepositPerByte = ConstU128<10>;
+	type StringLimit = ConstU32<128>;
+	type KeyLimit = ConstU32<32>;
+	type ValueLimit = ConstU32<64>;
+	type WeightInfo = pallet_uniques::weights::SubstrateWeight<Runtime>;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
 /// Configure the pallet-mydropbox in pallets/mydropbox.
 impl pallet_mydropbox::Config for Runtime {
 	type Event = Event;
@@ -272,6 +307,50 @@ impl pallet_mydropbox::Config for Runtime {
 	type MaxFilesUploaded = frame_support::pallet_prelude::ConstU32<100>;
 	type CostPerByte = ConstU64<50>;
 	type FileSizeLimit = ConstU64<250>;
+	type MaxPageSize = ConstU32<100>;
+	type MaxPageScan = ConstU32<1_000>;
+	type MinProviderStake = ConstU128<1_000_000>;
+	type UnbondingPeriod = ConstU32<{ 7 * DAYS }>;
+	type MaxProvidersPerFile = ConstU32<10>;
+	type MaxFilesPerProvider = ConstU32<1_000>;
+	type Randomness = RandomnessCollectiveFlip;
+	type ChallengePeriod = ConstU32<HOURS>;
+	type ChallengeWindow = ConstU32<{ 10 * MINUTES }>;
+	type ChallengeSlash = ChallengeSlash;
+	type MaxChallengesPerBlock = ConstU32<16>;
+	type MaxChunkSize = ConstU32<{ 64 * 1024 }>;
+	type MaxProofLength = ConstU32<32>;
+	type AvailabilityCheckInterval = ConstU32<{ 10 * MINUTES }>;
+	type MaxAvailabilityChecks = ConstU32<8>;
+	type AvailabilityGateway = AvailabilityGateway;
+	type UnsignedPriority = ConstU64<{ TransactionPriority::MAX / 2 }>;
+	type PalletId = DropboxPalletId;
+	type ReplicationPeriod = ConstU32<{ 6 * HOURS }>;
+	type MaxReplicationCandidates = ConstU32<16>;
+	type RentPerByte = ConstU128<1>;
+	type RentGracePeriod = ConstU32<{ 7 * DAYS }>;
+	type InitialRentPeriod = ConstU32<{ 7 * DAYS }>;
+	type DefaultQuota = ConstU64<{ 1024 * 1024 * 1024 }>;
+	type UploadFeePerByte = ConstU128<10>;
+	type DuplicateUploadFee = DuplicateUploadFee;
+	type MaxVersions = ConstU32<16>;
+	type MaxEnvelopeLength = ConstU32<512>;
+	type MaxKeyEnvelopes = ConstU32<256>;
+	type KeyRequestTimeout = ConstU32<{ 6 * HOURS }>;
+	type MaxKeyRequestsPerBlock = ConstU32<64>;
+	type Nfts = Uniques;
+	// Reserved for the file NFTs, away from the low ids users and the uniques benchmarks pick.
+	type NftCollection = ConstU32<{ u32::MAX }>;
+	type MaxOffersPerFile = ConstU32<16>;
+	type MaxAuctionsPerBlock = ConstU32<64>;
+	type MaxAuctionDuration = ConstU32<{ 30 * DAYS }>;
+	type TransferOfferTimeout = ConstU32<{ 7 * DAYS }>;
+	type MaxTransferOffersPerBlock = ConstU32<64>;
+	type MaxCoOwners = ConstU32<16>;
+	type MaxProposals = ConstU32<16>;
+	type MaxBatchSize = ConstU32<50>;
+	type FetchesPerLicense = FetchesPerLicense;
+	type WeightInfo = pallet_mydropbox::weights::SubstrateWeight<Runtime>;
 	// type Accountant = Dave;
 }
 
@@ -292,6 +371,7 @@ construct_runtime!(
 		Sudo: pallet_sudo,
 		// Include the custom logic from the pallet-mydropbox in the runtime.
 		TemplateModule: pallet_mydropbox,
+		Uniques: pallet_uniques,
 	}
 );
 
@@ -323,8 +403,18 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Storage migrations run on runtime upgrade, before the pallets' own hooks.
+pub type Migrations = (
+	pallet_mydropbox::migrations::v1::MigrateToV1<
+		Runtime,
+		<Runtime as pallet_uniques::Config>::CollectionDeposit,
+		<Runtime as pallet_uniques::Config>::ItemDeposit,
+	>,
+);
+
 #[cfg(feature = "runtime-benchmarks")]
 #[macro_use]
 extern crate frame_benchmarking;
@@ -336,7 +426,8 @@ mod benches {
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
-		[pallet_template, TemplateModule]
+		[pallet_uniques, Uniques]
+		[pallet_mydropbox, TemplateModule]
 	);
 }
 
@@ -471,6 +562,28 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_mydropbox_runtime_api::MydropboxApi<Block, AccountId, Hash, pallet_mydropbox::File<Runtime>> for Runtime {
+		fn list_files(
+			filter: pallet_mydropbox::FileFilter<AccountId>,
+			cursor: Option<Hash>,
+			limit: u32,
+		) -> (Vec<(Hash, pallet_mydropbox::File<Runtime>)>, Option<Hash>) {
+			TemplateModule::list_files(filter, cursor, limit)
+		}
+
+		fn verify_chunk(file_id: Hash, index: u32, chunk: Vec<u8>, proof: Vec<sp_core::H256>) -> bool {
+			TemplateModule::verify_file_chunk(&file_id, index, &chunk, &proof)
+		}
+
+		fn key_envelope(file_id: Hash, recipient: AccountId) -> Option<Vec<u8>> {
+			TemplateModule::key_envelope(file_id, recipient).map(|envelope| envelope.into_inner())
+		}
+
+		fn license(file_id: Hash, holder: AccountId, version: u32) -> Option<pallet_mydropbox::License> {
+			TemplateModule::licenses(file_id, (holder, version))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (